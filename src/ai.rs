@@ -0,0 +1,402 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use rand::{seq::IndexedRandom, Rng, SeedableRng};
+
+use crate::{
+    board::{BlockState, Board, OnBoard, Shape},
+    deck::{PlayerInputs, PlayerTarget, ReadInputs, TickInputs},
+    player::{PlayerKind, PlayerSetup},
+    prelude::*,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<AiDifficulty>().add_systems(
+        FixedFirst,
+        ai_inputs
+            .in_set(ReadInputs)
+            .after(crate::deck::take_inputs)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
+
+/// how well the ai plays
+#[derive(
+    Resource, Default, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+pub enum AiDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl AiDifficulty {
+    pub fn next(self) -> AiDifficulty {
+        match self {
+            AiDifficulty::Easy => AiDifficulty::Normal,
+            AiDifficulty::Normal => AiDifficulty::Hard,
+            AiDifficulty::Hard => AiDifficulty::Easy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AiDifficulty::Easy => "Easy",
+            AiDifficulty::Normal => "Normal",
+            AiDifficulty::Hard => "Hard",
+        }
+    }
+
+    /// ticks between each input
+    fn move_ticks(self) -> u32 {
+        match self {
+            AiDifficulty::Easy => 8,
+            AiDifficulty::Normal => 4,
+            AiDifficulty::Hard => 1,
+        }
+    }
+
+    /// chance of taking one of the better placements at random instead of the best
+    fn mistakes(self) -> f64 {
+        match self {
+            AiDifficulty::Easy => 0.4,
+            AiDifficulty::Normal => 0.1,
+            AiDifficulty::Hard => 0.,
+        }
+    }
+}
+
+/// a player with this is played by the ai instead of a person
+#[derive(Component)]
+pub struct AiPlayer {
+    pub difficulty: AiDifficulty,
+    /// the shape being placed and where it is going
+    plan: Option<(Entity, Placement)>,
+    wait: u32,
+    /// kept apart from `GameRng` so the ai does not change what the game would have done
+    rng: rand::rngs::StdRng,
+}
+
+impl AiPlayer {
+    pub fn new(difficulty: AiDifficulty) -> Self {
+        AiPlayer {
+            difficulty,
+            plan: None,
+            wait: 0,
+            rng: rand::rngs::StdRng::from_os_rng(),
+        }
+    }
+}
+
+/// weights for each part of the score given to a placement
+#[derive(Clone, Copy)]
+pub struct Heuristic {
+    pub holes: f32,
+    pub aggregate_height: f32,
+    pub bumpiness: f32,
+    pub lines: f32,
+}
+
+impl Default for Heuristic {
+    fn default() -> Self {
+        Heuristic {
+            holes: -0.36,
+            aggregate_height: -0.51,
+            bumpiness: -0.18,
+            lines: 0.76,
+        }
+    }
+}
+
+/// where a shape is and which way round, two shapes with the same placement cover the same cells
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Placement {
+    center: IVec2,
+    blocks: Vec<IVec2>,
+}
+
+impl Placement {
+    pub fn new(center: IVec2, mut blocks: Vec<IVec2>) -> Self {
+        blocks.sort_by_key(|block| (block.x, block.y));
+        Placement { center, blocks }
+    }
+
+    pub fn of(shape: &Shape) -> Self {
+        Placement::new(shape.center, shape.blocks.clone())
+    }
+
+    pub fn center(&self) -> IVec2 {
+        self.center
+    }
+
+    /// same blocks facing the same way, wherever they are
+    pub fn same_turn(&self, other: &Placement) -> bool {
+        self.blocks == other.blocks
+    }
+
+    fn cells(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.blocks.iter().map(|block| self.center + block)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AiMove {
+    Left,
+    Right,
+    Rotate,
+    Down,
+}
+
+impl AiMove {
+    const ALL: [AiMove; 4] = [AiMove::Left, AiMove::Right, AiMove::Rotate, AiMove::Down];
+
+    pub fn input(self) -> TickInputs {
+        match self {
+            AiMove::Left => TickInputs::tap(PlayerInputs::MoveLeft),
+            AiMove::Right => TickInputs::tap(PlayerInputs::MoveRight),
+            AiMove::Rotate => TickInputs::tap(PlayerInputs::Rotate),
+            AiMove::Down => TickInputs::hold(PlayerInputs::MoveDown),
+        }
+    }
+
+    /// the shape after making this move, if there is room for it
+    fn apply(self, shape: &Shape, board: &Board) -> Option<Shape> {
+        let mut shape = shape.clone();
+        let offset = match self {
+            AiMove::Left => IVec2::NEG_X,
+            AiMove::Right => IVec2::X,
+            AiMove::Down => IVec2::NEG_Y,
+            AiMove::Rotate => {
+                let kick = shape.kick(board)?;
+                shape.blocks = shape
+                    .blocks
+                    .iter()
+                    .map(|block| shape.rotated(*block))
+                    .collect();
+                shape.center += kick;
+                return Some(shape);
+            }
+        };
+        if !shape.can_translate(board, offset) {
+            return None;
+        }
+        shape.center += offset;
+        Some(shape)
+    }
+}
+
+/// the board as it would be without `shape` on it
+pub fn without_shape(board: &Board, shape: &Shape) -> Board {
+    let mut board = board.clone();
+    for block in shape.blocks.iter() {
+        if let BlockState::Contains(_) = board.get(shape.center + block) {
+            board.clear(shape.center + block);
+        }
+    }
+    board
+}
+
+/// every placement the shape can be moved to, with the first move on the way to it
+/// the first move is `None` for where the shape already is
+pub fn reachable(shape: &Shape, board: &Board) -> HashMap<Placement, (Shape, Option<AiMove>)> {
+    let mut found = HashMap::new();
+    let mut queue = VecDeque::new();
+    found.insert(Placement::of(shape), (shape.clone(), None));
+    queue.push_back((shape.clone(), None));
+    while let Some((current, first)) = queue.pop_front() {
+        for step in AiMove::ALL {
+            let Some(next) = step.apply(&current, board) else {
+                continue;
+            };
+            let placement = Placement::of(&next);
+            if found.contains_key(&placement) {
+                continue;
+            }
+            let first = first.or(Some(step));
+            found.insert(placement, (next.clone(), first));
+            queue.push_back((next, first));
+        }
+    }
+    found
+}
+
+/// placements where the shape would stop falling
+pub fn resting(shape: &Shape, board: &Board) -> Vec<Placement> {
+    reachable(shape, board)
+        .into_iter()
+        .filter(|(_, (shape, _))| !shape.can_translate(board, IVec2::NEG_Y))
+        .map(|(placement, _)| placement)
+        .collect()
+}
+
+/// `shape` turned `rotation` quarter turns where it is
+pub fn turned(shape: &Shape, rotation: u32) -> Shape {
+    let mut turned = shape.clone();
+    for _ in 0..rotation % 4 {
+        turned.blocks = turned
+            .blocks
+            .iter()
+            .map(|block| turned.rotated(*block))
+            .collect();
+    }
+    turned
+}
+
+/// the resting placement in column `x` turned `rotation` quarter turns, `None` if it can't get there
+/// `y` picks a spot under an overhang, otherwise it is where the shape would drop to
+pub fn find_placement(
+    shape: &Shape,
+    board: &Board,
+    x: i32,
+    y: Option<i32>,
+    rotation: u32,
+) -> Option<Placement> {
+    let facing = Placement::of(&turned(shape, rotation));
+    reachable(shape, board)
+        .into_iter()
+        .filter(|(placement, (placed, _))| {
+            placement.same_turn(&facing)
+                && placement.center().x == x
+                && !placed.can_translate(board, IVec2::NEG_Y)
+                && y.is_none_or(|y| placement.center().y == y)
+        })
+        .map(|(placement, _)| placement)
+        // dropped straight down it stops on the highest spot
+        .max_by_key(|placement| placement.center().y)
+}
+
+/// the input that moves `shape` one step towards `target`, dropping it once it is there
+/// `board` should not have the shape on it
+pub fn step_towards(shape: &Shape, board: &Board, target: &Placement) -> Option<TickInputs> {
+    let (_, first) = reachable(shape, board).remove(target)?;
+    Some(first.unwrap_or(AiMove::Down).input())
+}
+
+impl Heuristic {
+    /// how good the board is after locking the shape at `placement`, higher is better
+    pub fn score(&self, board: &Board, placement: &Placement) -> f32 {
+        let (width, hight) = (board.width(), board.hight());
+        let mut filled = vec![false; (width * hight) as usize];
+        for y in 0..hight {
+            for x in 0..width {
+                filled[(y * width + x) as usize] = board.get(IVec2::new(x, y)) != BlockState::Empty;
+            }
+        }
+        for cell in placement.cells() {
+            if cell.x >= 0 && cell.x < width && cell.y >= 0 && cell.y < hight {
+                filled[(cell.y * width + cell.x) as usize] = true;
+            }
+        }
+        let mut rows = filled.chunks(width as usize).collect::<Vec<_>>();
+        let before = rows.len();
+        rows.retain(|row| !row.iter().all(|cell| *cell));
+        let lines = (before - rows.len()) as f32;
+
+        let mut heights = vec![0; width as usize];
+        let mut holes = 0;
+        for (x, height) in heights.iter_mut().enumerate() {
+            let mut seen_top = false;
+            for (y, row) in rows.iter().enumerate().rev() {
+                if row[x] {
+                    if !seen_top {
+                        *height = y as i32 + 1;
+                        seen_top = true;
+                    }
+                } else if seen_top {
+                    holes += 1;
+                }
+            }
+        }
+        let aggregate_height = heights.iter().sum::<i32>() as f32;
+        let bumpiness = heights
+            .windows(2)
+            .map(|pair| (pair[0] - pair[1]).abs())
+            .sum::<i32>() as f32;
+        self.holes * holes as f32
+            + self.aggregate_height * aggregate_height
+            + self.bumpiness * bumpiness
+            + self.lines * lines
+    }
+}
+
+fn choose_placement(
+    shape: &Shape,
+    board: &Board,
+    difficulty: AiDifficulty,
+    rng: &mut impl Rng,
+) -> Option<Placement> {
+    let heuristic = Heuristic::default();
+    let mut options = resting(shape, board)
+        .into_iter()
+        .map(|placement| (heuristic.score(board, &placement), placement))
+        .collect::<Vec<_>>();
+    options.sort_by(|a, b| b.0.total_cmp(&a.0));
+    if rng.random_bool(difficulty.mistakes()) {
+        let top = options.len().min(4);
+        return options[..top].choose(rng).map(|(_, p)| p.clone());
+    }
+    options.into_iter().next().map(|(_, placement)| placement)
+}
+
+fn ai_inputs(
+    mut players: Query<(Entity, &mut AiPlayer, &mut TickInputs, &Board)>,
+    target: Query<(Entity, &Shape, &OnBoard), With<PlayerTarget>>,
+) {
+    for (player, mut ai, mut inputs, board) in &mut players {
+        let shape = target
+            .iter()
+            .find(|(_, _, on_board)| on_board.0 == player)
+            .map(|(entity, shape, _)| (entity, shape));
+        *inputs = plan_move(&mut ai, board, shape);
+    }
+}
+
+/// the input that gets the shape one step closer to where the ai wants it
+fn plan_move(ai: &mut AiPlayer, board: &Board, shape: Option<(Entity, &Shape)>) -> TickInputs {
+    let Some((entity, shape)) = shape else {
+        ai.plan = None;
+        return TickInputs::default();
+    };
+    if ai.wait > 0 {
+        ai.wait -= 1;
+        return TickInputs::default();
+    }
+    let board = without_shape(board, shape);
+    let mut routes = reachable(shape, &board);
+    let planned = ai
+        .plan
+        .as_ref()
+        .filter(|(planned, target)| *planned == entity && routes.contains_key(target));
+    let target = match planned {
+        Some((_, target)) => target.clone(),
+        // a new shape or the old plan can't be reached any more
+        None => {
+            let Some(target) = choose_placement(shape, &board, ai.difficulty, &mut ai.rng) else {
+                return TickInputs::default();
+            };
+            ai.plan = Some((entity, target.clone()));
+            target
+        }
+    };
+    let Some((_, first)) = routes.remove(&target) else {
+        return TickInputs::default();
+    };
+    ai.wait = ai.difficulty.move_ticks();
+    // already there so drop it in place
+    first.unwrap_or(AiMove::Down).input()
+}
+
+/// watch the ai play an endless game
+pub fn start_demo(
+    mut commands: Commands,
+    difficulty: Res<AiDifficulty>,
+    mut mode: ResMut<crate::mode::GameMode>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    commands.remove_resource::<crate::run::Run>();
+    commands.remove_resource::<crate::dig::Dig>();
+    commands.insert_resource(PlayerSetup::single(PlayerKind::Ai(*difficulty)));
+    *mode = crate::mode::GameMode::Endless;
+    state.set(GameState::Playing);
+}
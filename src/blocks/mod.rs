@@ -1,14 +1,17 @@
 mod powers;
+mod skin;
 use bevy::{platform_support::collections::HashSet, prelude::*};
 pub use powers::*;
+pub use skin::{BlockImage, BlockSkin, BlockSprites};
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins(powers::plugin);
+    app.add_plugins((powers::plugin, skin::plugin));
 }
 
 #[derive(Clone, Component)]
 pub struct Block {
-    pub shape: Entity,
+    /// the shape the block moves with, static blocks placed by the board setup have none
+    pub shape: Option<Entity>,
     pub moved: bool,
     pub effects: HashSet<Effect>,
 }
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use super::Block;
+
+use super::Effect;
+
+use super::Power;
+
+/// how far from the bomb blocks are destroyed when its row is cleared
+pub const BOMB_RADIUS: i32 = 2;
+
+#[derive(Component)]
+pub struct Bomb;
+
+impl Plugin for Bomb {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, add_bomb)
+            .init_resource::<BombAssets>();
+    }
+}
+
+#[derive(Resource)]
+struct BombAssets {
+    image: Handle<Image>,
+}
+
+impl FromWorld for BombAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        BombAssets {
+            image: asset_server.load(Power::Bomb.icon()),
+        }
+    }
+}
+
+fn add_bomb(
+    mut commands: Commands,
+    mut added: Query<(Entity, &mut Block), (With<Block>, Added<Bomb>)>,
+    assets: Res<BombAssets>,
+) {
+    for (entity, mut block) in &mut added {
+        commands.entity(entity).with_children(|c| {
+            c.spawn((
+                Sprite {
+                    image: assets.image.clone(),
+                    color: bevy::color::palettes::css::ORANGE_RED.into(),
+                    ..Default::default()
+                },
+                Transform::from_translation(Vec3::Z),
+            ));
+        });
+        block.effects.insert(Effect::Bomb);
+    }
+}
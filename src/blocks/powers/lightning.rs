@@ -4,6 +4,8 @@ use super::Block;
 
 use super::Effect;
 
+use super::Power;
+
 #[derive(Component)]
 pub struct Lightning;
 
@@ -23,7 +25,7 @@ impl FromWorld for LightningAssets {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
         LightningAssets {
-            image: asset_server.load("icons/bolt.png"),
+            image: asset_server.load(Power::Lightning.icon()),
         }
     }
 }
@@ -1,15 +1,58 @@
+mod bomb;
 mod lightning;
 
 use bevy::prelude::*;
+pub use bomb::*;
 pub use lightning::*;
 
 use super::Block;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(Lightning);
+    app.add_plugins((Lightning, Bomb));
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Effect {
     Fast,
+    Bomb,
+}
+
+/// a power that can be attached to a block of a shape in the deck
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
+pub enum Power {
+    Lightning,
+    Bomb,
+}
+
+impl Power {
+    pub const ALL: [Power; 2] = [Power::Lightning, Power::Bomb];
+
+    /// add the component for this power to a block
+    pub fn insert(self, block: &mut EntityCommands) {
+        match self {
+            Power::Lightning => block.insert(Lightning),
+            Power::Bomb => block.insert(Bomb),
+        };
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Power::Lightning => "Lightning",
+            Power::Bomb => "Bomb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Power> {
+        Power::ALL
+            .into_iter()
+            .find(|power| power.name().eq_ignore_ascii_case(name))
+    }
+
+    /// path of the icon drawn over blocks with this power
+    pub fn icon(self) -> &'static str {
+        match self {
+            Power::Lightning => "icons/bolt.png",
+            Power::Bomb => "icons/bomb.png",
+        }
+    }
 }
@@ -0,0 +1,171 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_pkv::PkvStore;
+
+use super::Block;
+use crate::{
+    board::{BlockState, Board, OnBoard, Shape},
+    prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BlockImage>()
+        .init_resource::<BlockSkin>()
+        .add_systems(PostUpdate, update_tiles)
+        .add_systems(
+            Update,
+            (change_skin, save_skin).run_if(resource_changed::<BlockSkin>),
+        );
+}
+
+/// how blocks are drawn
+#[derive(Resource, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum BlockSkin {
+    /// every block is its own tinted square
+    Classic,
+    /// tiles from an atlas picked by which sides touch the rest of the shape
+    Connected,
+}
+
+impl BlockSkin {
+    pub fn next(self) -> BlockSkin {
+        match self {
+            BlockSkin::Classic => BlockSkin::Connected,
+            BlockSkin::Connected => BlockSkin::Classic,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BlockSkin::Classic => "Classic",
+            BlockSkin::Connected => "Connected",
+        }
+    }
+}
+
+impl FromWorld for BlockSkin {
+    fn from_world(world: &mut World) -> Self {
+        let store = world.resource::<PkvStore>();
+        if let Ok(skin) = store.get(DataKeys::BlockSkin) {
+            skin
+        } else {
+            BlockSkin::Classic
+        }
+    }
+}
+
+fn save_skin(mut store: ResMut<PkvStore>, skin: Res<BlockSkin>) {
+    if let Err(e) = store.set(DataKeys::BlockSkin, &*skin) {
+        error!("Failed to save block skin: {e:?}");
+    };
+}
+
+#[derive(Resource)]
+pub struct BlockImage {
+    image: Handle<Image>,
+    connected: Handle<Image>,
+    atlas: Handle<TextureAtlasLayout>,
+}
+
+impl FromWorld for BlockImage {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        BlockImage {
+            image: asset_server.load("block.png"),
+            connected: asset_server.load("skins/connected.png"),
+            atlas: asset_server.add(TextureAtlasLayout::from_grid(
+                UVec2::splat(32),
+                4,
+                4,
+                None,
+                None,
+            )),
+        }
+    }
+}
+
+impl BlockImage {
+    pub fn sprite(&self, skin: BlockSkin, color: Color) -> Sprite {
+        match skin {
+            BlockSkin::Classic => Sprite {
+                image: self.image.clone(),
+                color,
+                ..Default::default()
+            },
+            BlockSkin::Connected => Sprite {
+                image: self.connected.clone(),
+                color,
+                texture_atlas: Some(TextureAtlas {
+                    layout: self.atlas.clone(),
+                    index: 0,
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// the image and skin needed to draw a new block
+#[derive(SystemParam)]
+pub struct BlockSprites<'w> {
+    image: Res<'w, BlockImage>,
+    skin: Res<'w, BlockSkin>,
+}
+
+impl BlockSprites<'_> {
+    pub fn sprite(&self, color: Color) -> Sprite {
+        self.image.sprite(*self.skin, color)
+    }
+}
+
+/// which sides of a block touch another block of its shape, up 1 right 2 down 4 left 8
+/// this is also the index of its tile in the connected atlas
+pub fn neighbour_mask(shape: &Shape, block: IVec2) -> usize {
+    [IVec2::Y, IVec2::X, IVec2::NEG_Y, IVec2::NEG_X]
+        .into_iter()
+        .enumerate()
+        .filter(|(_, side)| shape.blocks.contains(&(block + side)))
+        .fold(0, |mask, (bit, _)| mask | 1 << bit)
+}
+
+/// shapes change whenever they move, rotate, split or lose blocks to a line clear
+fn update_tiles(
+    shapes: Query<(&Shape, &OnBoard), Changed<Shape>>,
+    boards: Query<&Board>,
+    mut sprites: Query<&mut Sprite, With<Block>>,
+    skin: Res<BlockSkin>,
+) {
+    if *skin != BlockSkin::Connected {
+        return;
+    }
+    for (shape, on_board) in &shapes {
+        let Ok(board) = boards.get(on_board.0) else {
+            continue;
+        };
+        for block in shape.blocks.iter() {
+            let BlockState::Contains(entity) = board.get(shape.center + block) else {
+                continue;
+            };
+            let Ok(mut sprite) = sprites.get_mut(entity) else {
+                continue;
+            };
+            if let Some(atlas) = sprite.texture_atlas.as_mut() {
+                atlas.index = neighbour_mask(shape, *block);
+            }
+        }
+    }
+}
+
+fn change_skin(
+    skin: Res<BlockSkin>,
+    image: Res<BlockImage>,
+    mut blocks: Query<&mut Sprite, With<Block>>,
+    mut shapes: Query<&mut Shape>,
+) {
+    for mut sprite in &mut blocks {
+        *sprite = image.sprite(*skin, sprite.color);
+    }
+    // get every tile picked again
+    for mut shape in &mut shapes {
+        shape.set_changed();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    blocks::{Block, Effect},
+    blocks::{Block, BlockSprites, Effect, Power, BOMB_RADIUS},
     deck::PlayerTarget,
     prelude::*,
     GameState,
@@ -11,8 +11,12 @@ use bevy::{
 use indexmap::IndexSet;
 use rand::Rng;
 
-// create a resouse that holds the current boaed state linke each position to an entity or none if it is empty
-#[derive(Resource)]
+mod text;
+pub use text::{BoardDump, BoardText, BoardTextError, TextCell};
+
+// create a component that holds a players boaed state linke each position to an entity or none if it is empty
+#[derive(Component, Clone)]
+#[require(LineInfo)]
 pub struct Board {
     width: i32,
     hight: i32,
@@ -21,22 +25,41 @@ pub struct Board {
     has_moved: bool,
 }
 
-fn clear_changed(mut board: ResMut<Board>) {
-    board.has_moved = false;
+fn clear_changed(mut boards: Query<&mut Board>) {
+    for mut board in &mut boards {
+        board.has_moved = false;
+    }
 }
 
+/// the board a shape is falling on
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OnBoard(pub Entity);
+
 impl Default for Board {
     fn default() -> Self {
+        Board::new(10, 20)
+    }
+}
+impl Board {
+    pub fn new(width: i32, hight: i32) -> Self {
         Board {
-            width: 10,
-            hight: 20,
-            board: vec![None; 10 * 20],
+            width,
+            hight,
+            board: vec![None; (width * hight) as usize],
             changed: HashSet::with_hasher(FixedHasher),
             has_moved: false,
         }
     }
-}
-impl Board {
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+    pub fn hight(&self) -> i32 {
+        self.hight
+    }
+    /// something on the board moved this step, clears are only done once it settles
+    pub fn has_moved(&self) -> bool {
+        self.has_moved
+    }
     pub fn get(&self, block: IVec2) -> BlockState {
         let IVec2 { x, y } = block;
         if x >= self.width || y >= self.hight {
@@ -57,13 +80,49 @@ impl Board {
         self.has_moved = true;
         self.board[(block.y * self.width + block.x) as usize] = None;
     }
+    /// move every cell up by `rows` and return how many rows moved, negative rows move nothing
+    /// `None` and nothing changes if that would push blocks off the top, or more rows than the board is tall
+    /// shapes on the board have to have their center moved up by the rows returned
+    pub fn push_up(&mut self, rows: i32) -> Option<i32> {
+        let rows = rows.max(0);
+        if rows > self.hight {
+            return None;
+        }
+        let top = ((self.hight - rows) * self.width) as usize;
+        if self.board[top..].iter().any(Option::is_some) {
+            return None;
+        }
+        // the empty rows at the top wrap round to the bottom
+        self.board.rotate_right((rows * self.width) as usize);
+        for y in 0..self.hight {
+            for x in 0..self.width {
+                self.changed.insert(IVec2::new(x, y));
+            }
+        }
+        self.has_moved = true;
+        Some(rows)
+    }
     pub fn take(&mut self, block: IVec2) -> Option<Entity> {
         self.changed.insert(block);
         self.has_moved = true;
-        std::mem::take(&mut self.board[(block.y * 10 + block.x) as usize])
+        std::mem::take(&mut self.board[(block.y * self.width + block.x) as usize])
     }
 }
 
+/// where a turning shape is tried when it does not fit in place, in order
+/// one cell to either side, then one or two rows down so a shape can turn in under an overhang
+const KICKS: [IVec2; 9] = [
+    IVec2::ZERO,
+    IVec2::new(-1, 0),
+    IVec2::new(1, 0),
+    IVec2::new(0, -1),
+    IVec2::new(-1, -1),
+    IVec2::new(1, -1),
+    IVec2::new(0, -2),
+    IVec2::new(-1, -2),
+    IVec2::new(1, -2),
+];
+
 #[derive(PartialEq, Eq)]
 pub enum BlockState {
     Empty,
@@ -83,28 +142,18 @@ fn update_moved(mut blocks: Query<&mut Block, Changed<Transform>>) {
     }
 }
 
-#[derive(Resource)]
-pub struct BlockImage(Handle<Image>);
-impl BlockImage {
-    #[inline(always)]
-    fn get(&self) -> Handle<Image> {
-        self.0.clone()
-    }
-}
-
-impl FromWorld for BlockImage {
-    fn from_world(world: &mut World) -> Self {
-        BlockImage(world.resource::<AssetServer>().load("block.png"))
-    }
-}
-
-#[derive(Component, Clone)]
+#[derive(Component, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Shape {
     pub split: bool,
     pub center: IVec2,
     pub blocks: Vec<IVec2>,
     pub color: Color,
     pub center_of_mass: Vec2,
+    /// the point blocks turn around when rotated, whole or half cells from the center
+    #[serde(default)]
+    pub pivot: Vec2,
+    /// powers given to the block at each index when the shape is spawned
+    pub powers: Vec<(usize, Power)>,
 }
 
 impl Shape {
@@ -151,69 +200,97 @@ impl Shape {
         true
     }
 
-    pub fn can_rotate(&self, board: &Board) -> bool {
-        let com = (self.center_of_mass * 2.).round() / 2.;
-        let mut can_move = true;
-        for block in self.blocks.iter() {
-            let next_block = block.as_vec2() - com;
+    /// where a block ends up after a quarter turn anticlockwise around the pivot
+    pub fn rotated(&self, block: IVec2) -> IVec2 {
+        let pivot = self.doubled_pivot();
+        IVec2::new(
+            (pivot.x + pivot.y) / 2 - block.y,
+            (pivot.y - pivot.x) / 2 + block.x,
+        )
+    }
+
+    /// the pivot scaled by two so it can sit on a cell corner,
+    /// `Deck::validate` turns away pivots that would put rotated blocks between cells
+    fn doubled_pivot(&self) -> IVec2 {
+        let pivot = (self.pivot * 2.).round().as_ivec2();
+        debug_assert!(
+            pivot.as_vec2() == self.pivot * 2. && (pivot.x + pivot.y) % 2 == 0,
+            "pivot {} is not on a cell centre or corner",
+            self.pivot
+        );
+        pivot
+    }
 
-            let mut next = (Vec2 {
-                x: -next_block.y - com.y,
-                y: next_block.x + com.x,
+    /// the offset the shape moves by when it turns, the first of `KICKS` it fits at
+    pub fn kick(&self, board: &Board) -> Option<IVec2> {
+        KICKS.into_iter().find(|kick| {
+            self.blocks.iter().all(|block| {
+                let next = self.rotated(*block) + kick;
+                self.blocks.contains(&next) || board.get(self.center + next) == BlockState::Empty
             })
-            .floor()
-            .as_ivec2();
+        })
+    }
 
-            if self.blocks.contains(&next) {
-                continue;
-            };
-            next += self.center;
-            match board.get(next) {
-                BlockState::Empty => {}
-                _ => can_move = false,
-            }
-        }
-        can_move
+    pub fn can_rotate(&self, board: &Board) -> bool {
+        self.kick(board).is_some()
     }
 
     pub fn rotate(&mut self, board: &mut Board) -> bool {
-        if !self.can_rotate(board) {
+        let Some(kick) = self.kick(board) else {
             return false;
-        }
-        let com = (self.center_of_mass * 2.).round() / 2.;
+        };
         let mut old = Vec::new();
         for block in self.blocks.iter() {
             let block = self.center + block;
-            old.push((board.take(block), block));
-        }
-        for (block, target) in self.blocks.iter_mut().zip(&mut old) {
-            let next_block = block.as_vec2() - com;
-            let next = Vec2 {
-                x: -next_block.y - com.y,
-                y: next_block.x + com.x,
-            }
-            .round()
-            .as_ivec2();
-
-            // let x = -block.y;
-            // block.y = block.x;
-            // block.x = x;
-            *block = next;
-            let block = self.center + next;
-            if let (Some(_), new) = target {
-                *new = block;
-            }
+            old.push(board.take(block));
         }
+        self.blocks = self
+            .blocks
+            .iter()
+            .map(|block| self.rotated(*block))
+            .collect();
+        self.center += kick;
         self.calc_center();
-        for (block, new) in old {
-            if let Some(target) = block {
-                // todo .expect("Blocks Should not be empty")
-                board.set(new, target);
+        for (block, target) in self.blocks.iter().zip(old) {
+            let block = self.center + block;
+            if let Some(target) = target {
+                board.set(block, target);
             }
         }
         true
     }
 
+    /// every block that can be reached from `start` by stepping between blocks of this shape
+    pub fn connected(&self, start: IVec2) -> Vec<IVec2> {
+        let mut to_check = IndexSet::with_capacity(self.blocks.len());
+        let mut checked = HashSet::new();
+        let mut valid = Vec::new();
+        to_check.insert(start);
+        while let Some(current) = to_check.pop() {
+            if self.blocks.contains(&current) {
+                valid.push(current);
+                checked.insert(current);
+                let up = current + IVec2::Y;
+                if !checked.contains(&up) {
+                    to_check.insert(up);
+                }
+                let down = current + IVec2::NEG_Y;
+                if !checked.contains(&down) {
+                    to_check.insert(down);
+                }
+                let right = current + IVec2::X;
+                if !checked.contains(&right) {
+                    to_check.insert(right);
+                }
+                let left = current + IVec2::NEG_X;
+                if !checked.contains(&left) {
+                    to_check.insert(left);
+                }
+            }
+        }
+        valid
+    }
+
     pub fn can_spawn(&self, board: &crate::board::Board) -> bool {
         for block in self.blocks.iter() {
             let block = self.center + block;
@@ -226,243 +303,452 @@ impl Shape {
 }
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, spawn_shape)
+    app.init_resource::<GameClock>()
+        .add_systems(FixedUpdate, spawn_shape.after(spawn_next))
         .add_systems(PostUpdate, (update_board, update_moved).chain())
         .add_systems(
             FixedUpdate,
-            (apply_gravity, spawn_next, clear_line).run_if(in_state(GameState::Playing)),
+            (apply_gravity, spawn_next, clear_line)
+                .chain()
+                .run_if(in_state(GameState::Playing).and(on_step)),
         )
-        .add_systems(FixedFirst, clear_moved)
-        .init_resource::<BlockImage>()
-        .add_systems(PostUpdate, split_shape);
-    app.add_systems(FixedFirst, clear_changed)
-        .add_systems(FixedLast, score_line)
-        .init_resource::<LineInfo>();
+        .add_systems(
+            FixedFirst,
+            (advance_clock, (clear_moved, clear_changed).run_if(on_step)).chain(),
+        )
+        .add_systems(FixedPostUpdate, split_shape)
+        .add_systems(OnEnter(GameState::Playing), reset_clock);
+    app.add_systems(FixedLast, (score_line.run_if(on_step), score_destroyed))
+        .add_event::<BlocksDestroyed>()
+        .add_event::<LinesCleared>()
+        .add_event::<ToppedOut>()
+        .add_systems(FixedLast, game_over.run_if(in_state(GameState::Playing)))
+        .add_systems(OnExit(GameState::Playing), reset_board);
     app.register_required_components::<Block, Sprite>();
     #[cfg(debug_assertions)]
-    app.add_systems(Update, show_center_of_mass);
+    app.add_systems(
+        Update,
+        show_center_of_mass.run_if(resource_exists::<bevy::gizmos::config::GizmoConfigStore>),
+    );
 }
 
-fn apply_gravity(
-    mut shapes: Query<(Entity, &mut Shape)>,
+/// fixed ticks per gravity step before any level ups
+pub const STEP_TICKS: u32 = 10;
+
+/// counts fixed ticks, player input is read every tick but blocks only fall every `step_ticks`
+#[derive(Resource)]
+pub struct GameClock {
+    pub tick: u64,
+    pub step_ticks: u32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock {
+            tick: 0,
+            step_ticks: STEP_TICKS,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn is_step(&self) -> bool {
+        self.tick % self.step_ticks.max(1) as u64 == 0
+    }
+}
+
+/// run condition for systems that only happen when blocks fall
+pub fn on_step(clock: Res<GameClock>) -> bool {
+    clock.is_step()
+}
+
+pub(crate) fn advance_clock(mut clock: ResMut<GameClock>) {
+    clock.tick += 1;
+}
+
+pub(crate) fn reset_clock(mut clock: ResMut<GameClock>) {
+    *clock = GameClock::default();
+}
+
+pub(crate) fn apply_gravity(
+    mut shapes: Query<(Entity, &mut Shape, &OnBoard)>,
     mut target: Query<&mut PlayerTarget>,
-    mut board: ResMut<crate::board::Board>,
+    mut boards: Query<&mut Board>,
 ) {
-    for (entity, mut shape) in &mut shapes {
+    for (entity, mut shape, on_board) in &mut shapes {
+        let Ok(mut board) = boards.get_mut(on_board.0) else {
+            continue;
+        };
         if shape.translate(&mut board, IVec2::NEG_Y) {
             if let Ok(mut target) = target.get_mut(entity) {
                 target.last_y = 0;
                 target.moved = true;
+                target.rotated = false;
             }
         }
     }
 }
 
-fn spawn_shape(
+pub(crate) fn spawn_shape(
     mut commands: Commands,
-    shapes: Query<(Entity, &Shape), Added<Shape>>,
-    mut board: ResMut<crate::board::Board>,
-    block_image: Res<BlockImage>,
+    shapes: Query<(Entity, &Shape, &OnBoard), Added<Shape>>,
+    mut boards: Query<&mut Board>,
+    sprites: BlockSprites,
+    palette: Res<crate::palette::BlockPalette>,
+    other_modes: crate::mode::OtherModes,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::rng();
-    for (e, shape) in &shapes {
+    for (e, shape, on_board) in &shapes {
         if shape.split {
             continue;
         }
-        for block in shape.blocks.iter() {
+        let Ok(mut board) = boards.get_mut(on_board.0) else {
+            continue;
+        };
+        let color = palette.color(shape);
+        for (index, block) in shape.blocks.iter().enumerate() {
             let block = shape.center + block;
             let id = commands
                 .spawn((
                     Block {
-                        shape: e,
+                        shape: Some(e),
                         moved: true,
                         effects: HashSet::with_hasher(FixedHasher),
                     },
-                    Transform::from_translation((block * 64).as_vec2().extend(1.)),
-                    Sprite {
-                        image: block_image.get(),
-                        color: shape.color,
-                        ..Default::default()
-                    },
+                    Transform::from_translation((block * 32).as_vec2().extend(1.)),
+                    sprites.sprite(color),
                 ))
                 .id();
-            if rng.random_bool(0.1) {
-                commands.entity(id).insert(crate::blocks::Lightning);
+            commands.entity(on_board.0).add_child(id);
+            if let Some((_, power)) = shape.powers.iter().find(|(i, _)| *i == index) {
+                power.insert(&mut commands.entity(id));
+            } else if other_modes.run.is_none() && other_modes.puzzle.is_none() {
+                // runs and puzzles only get the powers their shapes were given
+                if rng.random_bool(0.1) {
+                    commands.entity(id).insert(crate::blocks::Lightning);
+                } else if rng.random_bool(0.05) {
+                    commands.entity(id).insert(crate::blocks::Bomb);
+                }
             }
             board.set(block, id);
         }
     }
 }
 
-fn update_board(mut blocks: Query<&mut Transform>, mut board: ResMut<Board>) {
-    for cell in std::mem::take(&mut board.changed) {
-        let BlockState::Contains(entity) = board.get(cell) else {
+fn update_board(
+    mut blocks: Query<&mut Transform, With<Block>>,
+    mut boards: Query<&mut Board>,
+    dump: BoardDump,
+) {
+    for mut board in &mut boards {
+        let mut invalid = false;
+        for cell in std::mem::take(&mut board.changed) {
+            let BlockState::Contains(entity) = board.get(cell) else {
+                continue;
+            };
+            let Ok(mut block) = blocks.get_mut(entity) else {
+                warn!("{cell} has invalid entity {entity}");
+                invalid = true;
+                continue;
+            };
+            block.translation = (cell * 32).extend(1).as_vec3();
+        }
+        if invalid {
+            debug!("board with invalid entities:\n{}", dump.dump(&board));
+        }
+    }
+}
+
+pub(crate) fn spawn_next(
+    active: Query<&OnBoard, With<PlayerTarget>>,
+    mut boards: Query<(Entity, &Board, &mut crate::deck::CurrentDeck)>,
+    mut commands: Commands,
+    mut topped_out: EventWriter<ToppedOut>,
+) {
+    'board: for (entity, board, mut deck) in &mut boards {
+        if board.has_moved || active.iter().any(|on_board| on_board.0 == entity) {
             continue;
         };
-        let Ok(mut block) = blocks.get_mut(entity) else {
-            warn!("{cell} has invalid entity {entity}");
+        // a fixed deck that has run out deals nothing more
+        let Some(mut shape) = deck.draw() else {
             continue;
         };
-        block.translation = (cell * 32).extend(1).as_vec3();
+        let center = IVec2::new(board.width / 2, board.hight - 1);
+        for y in 0..board.hight {
+            for x in 0..(board.width / 2) + 1 {
+                let next = center - IVec2::new(x, y);
+                shape.center = next;
+                if shape.can_spawn(board) {
+                    commands.spawn((shape, OnBoard(entity), PlayerTarget::default()));
+                    continue 'board;
+                }
+                let next = center - IVec2::new(-x, y);
+                shape.center = next;
+                if shape.can_spawn(board) {
+                    commands.spawn((shape, OnBoard(entity), PlayerTarget::default()));
+                    continue 'board;
+                }
+            }
+        }
+        warn!("Failed to find valid spawn for shape");
+        topped_out.write(ToppedOut { board: entity });
     }
 }
 
-fn spawn_next(
-    active: Query<(), With<PlayerTarget>>,
-    mut deck: ResMut<crate::deck::CurrentDeck>,
-    board: Res<Board>,
-    mut commands: Commands,
+/// sent when a board is too full to keep playing
+#[derive(Event)]
+pub struct ToppedOut {
+    pub board: Entity,
+}
+
+fn game_over(
+    mut events: EventReader<ToppedOut>,
+    players: Query<(Entity, &crate::player::Player)>,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<crate::ui::menus::Menu>>,
 ) {
-    if board.has_moved || active.single().is_ok() {
+    let Some(ToppedOut { board }) = events.read().last() else {
         return;
     };
-    let mut shape = deck.next();
-    let center = IVec2::new(board.width / 2, board.hight - 1);
-    for y in 0..board.hight {
-        for x in 0..(board.width / 2) + 1 {
-            let next = center - IVec2::new(x, y);
-            shape.center = next;
-            if shape.can_spawn(&board) {
-                commands.spawn((shape, PlayerTarget::default()));
-                return;
-            }
-            let next = center - IVec2::new(-x, y);
-            shape.center = next;
-            if shape.can_spawn(&board) {
-                commands.spawn((shape, PlayerTarget::default()));
-                return;
+    info!("Game Over");
+    if players.iter().count() > 1 {
+        for (entity, player) in &players {
+            if entity != *board {
+                info!("Player {} wins", player.index + 1);
             }
         }
     }
-    warn!("Failed to find valid spawn for shape");
+    state.set(GameState::InMenu);
+    menu.set(crate::ui::menus::Menu::Main);
 }
 
 fn clear_line(
-    mut board: ResMut<Board>,
+    mut boards: Query<(Entity, &mut Board, &mut LineInfo)>,
     mut shapes: Query<&mut Shape>,
     blocks: Query<&Block>,
     player: Query<(), With<PlayerTarget>>,
     mut commands: Commands,
-    mut score: ResMut<LineInfo>,
+    mut destroyed: EventWriter<BlocksDestroyed>,
+    mut lines: EventWriter<LinesCleared>,
 ) {
-    let mut found = 0;
-    'y: for y in 0..board.hight {
-        let mut fast = false;
-        let mut has_moving = false;
-        for x in 0..board.width {
-            match board.get(IVec2::new(x, y)) {
-                BlockState::Empty => {
-                    continue 'y;
-                }
-                BlockState::Contains(block) => {
-                    let Ok(block) = blocks.get(block) else {
-                        error!("{block} is not a block");
+    for (entity, mut board, mut score) in &mut boards {
+        let mut found = 0;
+        let mut bombs = Vec::new();
+        'y: for y in 0..board.hight {
+            let mut fast = false;
+            let mut has_moving = false;
+            for x in 0..board.width {
+                match board.get(IVec2::new(x, y)) {
+                    BlockState::Empty => {
                         continue 'y;
-                    };
-                    if block.moved {
-                        has_moving = true;
                     }
-                    if player.get(block.shape).is_ok() {
-                        has_moving = true;
-                    };
-                    if block.effects.contains(&Effect::Fast) {
-                        fast = true;
+                    BlockState::Contains(block) => {
+                        let Ok(block) = blocks.get(block) else {
+                            error!("{block} is not a block");
+                            continue 'y;
+                        };
+                        if block.moved {
+                            has_moving = true;
+                        }
+                        if block.shape.is_some_and(|shape| player.get(shape).is_ok()) {
+                            has_moving = true;
+                        };
+                        if block.effects.contains(&Effect::Fast) {
+                            fast = true;
+                        }
+                    }
+                    BlockState::OutOfBounds => {
+                        unreachable!("don't check out of bounds for lines")
                     }
-                }
-                BlockState::OutOfBounds => {
-                    unreachable!("don't check out of bounds for lines")
                 }
             }
-        }
-        if has_moving && !fast {
-            continue;
-        }
-        for x in 0..board.width {
-            let pos = IVec2::new(x, y);
-            let BlockState::Contains(entity) = board.get(pos) else {
-                error!("Line Has Empty Space");
+            if has_moving && !fast {
                 continue;
-            };
-            board.clear(pos);
-            let Ok(block) = blocks.get(entity) else {
-                error!("{entity} is not a block");
-                continue;
-            };
-            commands.entity(entity).despawn();
-            let Ok(mut shape) = shapes.get_mut(block.shape) else {
-                error!("{} is not a shape", block.shape);
-                continue;
-            };
-            let mut index = 0;
-            let pos = pos - shape.center;
-            for block in shape.blocks.iter() {
-                if *block == pos {
-                    break;
+            }
+            for x in 0..board.width {
+                let pos = IVec2::new(x, y);
+                let BlockState::Contains(entity) = board.get(pos) else {
+                    error!("Line Has Empty Space");
+                    continue;
+                };
+                if let Ok(block) = blocks.get(entity) {
+                    if block.effects.contains(&Effect::Bomb) {
+                        bombs.push(pos);
+                    }
                 }
-                index += 1;
+                remove_block(&mut board, pos, entity, &blocks, &mut shapes, &mut commands);
             }
-            shape.blocks.swap_remove(index);
-            if shape.blocks.is_empty() {
-                commands.entity(block.shape).despawn();
+            found += 1;
+        }
+        let mut count = 0;
+        for bomb in bombs {
+            for y in -BOMB_RADIUS..=BOMB_RADIUS {
+                for x in -BOMB_RADIUS..=BOMB_RADIUS {
+                    let offset = IVec2::new(x, y);
+                    if offset.length_squared() > BOMB_RADIUS * BOMB_RADIUS {
+                        continue;
+                    }
+                    let pos = bomb + offset;
+                    let BlockState::Contains(entity) = board.get(pos) else {
+                        continue;
+                    };
+                    remove_block(&mut board, pos, entity, &blocks, &mut shapes, &mut commands);
+                    count += 1;
+                }
             }
-            shape.calc_center();
         }
-        found += 1;
+        if count > 0 {
+            destroyed.write(BlocksDestroyed {
+                board: entity,
+                count,
+            });
+        }
+        if found > 0 {
+            score.chain += found;
+            lines.write(LinesCleared {
+                board: entity,
+                lines: found,
+            });
+        }
+    }
+}
+
+/// put a block on the board that is not part of any shape, it stays where it is until it is cleared
+pub fn spawn_static_block(
+    commands: &mut Commands,
+    entity: Entity,
+    board: &mut Board,
+    cell: IVec2,
+    sprite: Sprite,
+    power: Option<Power>,
+) -> Entity {
+    let block = Block {
+        shape: None,
+        moved: false,
+        effects: HashSet::with_hasher(FixedHasher),
+    };
+    spawn_board_block(commands, entity, board, cell, sprite, power, block)
+}
+
+/// put `block` on the board at `cell`, its shape has to already hold it if it has one
+pub fn spawn_board_block(
+    commands: &mut Commands,
+    entity: Entity,
+    board: &mut Board,
+    cell: IVec2,
+    sprite: Sprite,
+    power: Option<Power>,
+    block: Block,
+) -> Entity {
+    let block = commands
+        .spawn((
+            block,
+            Transform::from_translation((cell * 32).as_vec2().extend(1.)),
+            sprite,
+        ))
+        .id();
+    if let Some(power) = power {
+        power.insert(&mut commands.entity(block));
+    }
+    board.set(cell, block);
+    commands.entity(entity).add_child(block);
+    block
+}
+
+/// removes the block at `pos` from the board and from the shape that owns it
+/// the shape is despawned if it has no blocks left, otherwise `split_shape` will pick it up
+fn remove_block(
+    board: &mut Board,
+    pos: IVec2,
+    entity: Entity,
+    blocks: &Query<&Block>,
+    shapes: &mut Query<&mut Shape>,
+    commands: &mut Commands,
+) {
+    board.clear(pos);
+    let Ok(block) = blocks.get(entity) else {
+        error!("{entity} is not a block");
+        return;
+    };
+    commands.entity(entity).despawn();
+    let Some(shape_entity) = block.shape else {
+        return;
+    };
+    let Ok(mut shape) = shapes.get_mut(shape_entity) else {
+        error!("{shape_entity} is not a shape");
+        return;
+    };
+    let pos = pos - shape.center;
+    let Some(index) = shape.blocks.iter().position(|block| *block == pos) else {
+        error!("{pos} is not part of {shape_entity}");
+        return;
+    };
+    shape.blocks.swap_remove(index);
+    if shape.blocks.is_empty() {
+        commands.entity(shape_entity).despawn();
+    } else {
+        shape.calc_center();
     }
-    if found > 0 {
-        score.chain += found;
+}
+
+/// sent each tick that one or more lines are cleared on a board
+#[derive(Event)]
+pub struct LinesCleared {
+    pub board: Entity,
+    pub lines: i32,
+}
+
+/// sent when blocks are destroyed by something other than a line clear
+#[derive(Event)]
+pub struct BlocksDestroyed {
+    pub board: Entity,
+    pub count: i32,
+}
+
+fn score_destroyed(mut events: EventReader<BlocksDestroyed>, mut scores: Query<&mut Score>) {
+    for BlocksDestroyed { board, count } in events.read() {
+        if let Ok(mut score) = scores.get_mut(*board) {
+            score.0 += count;
+        }
     }
 }
 
-#[derive(Resource, Default)]
+#[derive(Component, Default)]
 struct LineInfo {
     chain: i32,
 }
 
-fn score_line(board: Res<Board>, mut line_info: ResMut<LineInfo>, mut score: ResMut<Score>) {
-    if board.has_moved {
-        return;
+/// despawn every shape, the blocks go with the board they are on
+fn reset_board(mut commands: Commands, shapes: Query<Entity, With<Shape>>) {
+    for entity in &shapes {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn score_line(mut boards: Query<(&Board, &mut LineInfo, &mut Score)>) {
+    for (board, mut line_info, mut score) in &mut boards {
+        if board.has_moved {
+            continue;
+        }
+        score.0 += line_info.chain * line_info.chain;
+        line_info.chain = 0;
     }
-    score.0 += line_info.chain * line_info.chain;
-    line_info.chain = 0;
 }
 
 fn split_shape(
-    mut shapes: Query<(&mut Shape, Option<&PlayerTarget>), Changed<Shape>>,
-    board: Res<Board>,
+    mut shapes: Query<(&mut Shape, Option<&PlayerTarget>, &OnBoard), Changed<Shape>>,
+    boards: Query<&Board>,
     mut blocks: Query<&mut Block>,
     mut commands: Commands,
 ) {
-    for (mut shape, target) in &mut shapes {
-        if shape.blocks.is_empty() {
+    for (mut shape, target, on_board) in &mut shapes {
+        let Some(first) = shape.blocks.first().copied() else {
             continue;
         };
-        let mut to_check = IndexSet::with_capacity(shape.blocks.len());
-        let mut checked = HashSet::new();
-        let mut valid = Vec::new();
-        to_check.insert(shape.blocks.first().cloned().expect("At least one block"));
-        while let Some(current) = to_check.pop() {
-            if shape.blocks.contains(&current) {
-                valid.push(current);
-                checked.insert(current);
-                let up = current + IVec2::Y;
-                if !checked.contains(&up) {
-                    to_check.insert(up);
-                }
-                let down = current + IVec2::NEG_Y;
-                if !checked.contains(&down) {
-                    to_check.insert(down);
-                }
-                let right = current + IVec2::X;
-                if !checked.contains(&right) {
-                    to_check.insert(right);
-                }
-                let left = current + IVec2::NEG_X;
-                if !checked.contains(&left) {
-                    to_check.insert(left);
-                }
-            }
-        }
+        let Ok(board) = boards.get(on_board.0) else {
+            continue;
+        };
+        let mut valid = shape.connected(first);
         if shape.blocks.len() != valid.len() {
             std::mem::swap(&mut shape.blocks, &mut valid);
             valid.retain(|block| !shape.blocks.contains(block));
@@ -473,9 +759,11 @@ fn split_shape(
                 blocks: valid.clone(),
                 color: shape.color,
                 center_of_mass: Vec2::ZERO,
+                pivot: shape.pivot,
+                powers: Vec::new(),
             };
             new_shape.calc_center();
-            let new = commands.spawn(new_shape).id();
+            let new = commands.spawn((new_shape, *on_board)).id();
             if let Some(target) = target {
                 commands.entity(new).insert(*target);
             }
@@ -489,29 +777,186 @@ fn split_shape(
                     error!("{entity:?} is not a block");
                     continue;
                 };
-                block.shape = new;
+                block.shape = Some(new);
             }
         }
     }
 }
 
 fn show_center_of_mass(
-    blocks: Query<&Transform>,
-    shapes: Query<&Shape>,
-    board: Res<Board>,
+    shapes: Query<(&Shape, &OnBoard)>,
+    boards: Query<&GlobalTransform, With<Board>>,
     mut gizmos: Gizmos,
 ) {
-    for shape in &shapes {
+    for (shape, on_board) in &shapes {
+        let offset = boards
+            .get(on_board.0)
+            .map_or(Vec2::ZERO, |board| board.translation().truncate());
         let center = shape.center;
         gizmos.rect_2d(
-            Isometry2d::from_translation(center.as_vec2() * 32.),
+            Isometry2d::from_translation(offset + center.as_vec2() * 32.),
             Vec2::splat(16.),
             Color::WHITE,
         );
         gizmos.rect_2d(
-            Isometry2d::from_translation((center.as_vec2() + shape.center_of_mass) * 32.),
+            Isometry2d::from_translation(offset + (center.as_vec2() + shape.center_of_mass) * 32.),
             Vec2::splat(16.),
             bevy::color::palettes::css::RED,
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+
+    fn shape(blocks: &[(i32, i32)], pivot: Vec2) -> Shape {
+        let mut shape = Shape {
+            split: false,
+            center: IVec2::new(5, 10),
+            blocks: blocks.iter().map(|(x, y)| IVec2::new(*x, *y)).collect(),
+            color: Color::WHITE,
+            center_of_mass: Vec2::ZERO,
+            pivot,
+            powers: Vec::new(),
+        };
+        shape.calc_center();
+        shape
+    }
+
+    fn pentominoes() -> Vec<Shape> {
+        vec![
+            // I, odd length so the pivot sits on a cell
+            shape(&[(-2, 0), (-1, 0), (0, 0), (1, 0), (2, 0)], Vec2::ZERO),
+            // L, pivot on a cell corner
+            shape(
+                &[(0, 0), (0, 1), (0, 2), (0, -1), (1, -1)],
+                Vec2::new(0.5, 0.5),
+            ),
+            // P, pivot in the middle of its square
+            shape(
+                &[(0, 0), (1, 0), (0, 1), (1, 1), (0, -1)],
+                Vec2::new(0.5, 0.5),
+            ),
+            // X
+            shape(&[(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)], Vec2::ZERO),
+        ]
+    }
+
+    fn place(shape: &Shape, board: &mut Board) {
+        for (index, block) in shape.blocks.iter().enumerate() {
+            board.set(shape.center + block, Entity::from_raw(index as u32));
+        }
+    }
+
+    fn cells(shape: &Shape) -> Vec<IVec2> {
+        let mut cells = shape
+            .blocks
+            .iter()
+            .map(|block| shape.center + block)
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    #[test]
+    fn pentominoes_are_valid() {
+        let deck = Deck::from_shapes(pentominoes());
+        assert!(deck.validate(&Board::new(10, 20)).is_empty());
+    }
+
+    #[test]
+    fn four_turns_come_back() {
+        let shapes = Deck::classic().shapes().to_vec();
+        for mut shape in shapes.into_iter().chain(pentominoes()) {
+            shape.center = IVec2::new(5, 10);
+            let mut board = Board::new(10, 20);
+            place(&shape, &mut board);
+            let start = cells(&shape);
+            for _ in 0..4 {
+                assert!(shape.can_rotate(&board));
+                assert!(shape.rotate(&mut board));
+                for cell in cells(&shape) {
+                    assert!(matches!(board.get(cell), BlockState::Contains(_)));
+                }
+            }
+            assert_eq!(cells(&shape), start);
+        }
+    }
+
+    #[test]
+    fn can_rotate_agrees_with_rotate() {
+        let shapes = Deck::classic().shapes().to_vec();
+        for mut shape in shapes.into_iter().chain(pentominoes()) {
+            for x in 0..10 {
+                for y in 0..4 {
+                    shape.center = IVec2::new(x, y);
+                    let mut board = Board::new(10, 20);
+                    if !shape.can_spawn(&board) {
+                        continue;
+                    }
+                    place(&shape, &mut board);
+                    let before = cells(&shape);
+                    let allowed = shape.can_rotate(&board);
+                    let mut turned = shape.clone();
+                    assert_eq!(turned.rotate(&mut board), allowed);
+                    if !allowed {
+                        assert_eq!(cells(&turned), before);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn push_up_stops_at_the_top() {
+        let mut board = Board::new(4, 6);
+        assert_eq!(board.push_up(7), None);
+        assert_eq!(board.push_up(-2), Some(0));
+        assert_eq!(board.push_up(6), Some(6));
+        board.set(IVec2::new(1, 0), Entity::from_raw(0));
+        assert_eq!(board.push_up(5), Some(5));
+        assert!(matches!(
+            board.get(IVec2::new(1, 5)),
+            BlockState::Contains(_)
+        ));
+        assert_eq!(board.push_up(1), None);
+    }
+
+    #[test]
+    fn t_kicks_under_an_overhang() {
+        let text = text::BoardText::parse(
+            r"
+            ...#......
+            ####.#####
+            ###..#####
+            ####.#####
+            ",
+        )
+        .unwrap();
+        let mut board = Board::new(10, 20);
+        for (index, (cell, _)) in text.cells.iter().enumerate() {
+            board.set(*cell, Entity::from_raw(100 + index as u32));
+        }
+        let mut t = Deck::classic().shapes()[4].clone();
+        t.center = IVec2::new(5, 3);
+        place(&t, &mut board);
+        assert_eq!(t.kick(&board), Some(IVec2::new(-1, -2)));
+        assert!(t.rotate(&mut board));
+        assert_eq!(
+            cells(&t),
+            vec![
+                IVec2::new(4, 0),
+                IVec2::new(3, 1),
+                IVec2::new(4, 1),
+                IVec2::new(4, 2)
+            ]
+        );
+        let target = PlayerTarget {
+            rotated: true,
+            ..default()
+        };
+        assert!(target.spun(&t, &board));
+    }
+}
@@ -0,0 +1,375 @@
+//! boards as text, one character a cell with the top row first
+//! `.` is empty, `#` is a static block and `@` is a block of the active shape
+//! blocks with a power use the first letter of its name instead, lower case for static blocks and upper case for the active shape
+//! `?` marks a cell holding an entity that is not a block, the parser never accepts it
+
+use bevy::{
+    ecs::system::SystemParam,
+    platform_support::{collections::HashSet, hash::FixedHasher},
+    prelude::*,
+};
+
+use super::{spawn_board_block, BlockState, Board, OnBoard, Shape};
+use crate::{
+    blocks::{Block, BlockImage, BlockSkin, Bomb, Lightning, Power},
+    deck::PlayerTarget,
+    garbage::GARBAGE_COLOR,
+};
+
+const EMPTY: char = '.';
+const STATIC: char = '#';
+const ACTIVE: char = '@';
+const INVALID: char = '?';
+
+/// what a single cell of a text board holds
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextCell {
+    Empty,
+    Static(Option<Power>),
+    Active(Option<Power>),
+}
+
+impl TextCell {
+    fn symbol(self) -> char {
+        match self {
+            TextCell::Empty => EMPTY,
+            TextCell::Static(None) => STATIC,
+            TextCell::Active(None) => ACTIVE,
+            TextCell::Static(Some(power)) => marker(power).to_ascii_lowercase(),
+            TextCell::Active(Some(power)) => marker(power).to_ascii_uppercase(),
+        }
+    }
+
+    fn from_symbol(symbol: char) -> Option<TextCell> {
+        match symbol {
+            EMPTY => return Some(TextCell::Empty),
+            STATIC => return Some(TextCell::Static(None)),
+            ACTIVE => return Some(TextCell::Active(None)),
+            _ => {}
+        }
+        let power = Power::ALL
+            .into_iter()
+            .find(|power| marker(*power).eq_ignore_ascii_case(&symbol))?;
+        if symbol.is_ascii_uppercase() {
+            Some(TextCell::Active(Some(power)))
+        } else {
+            Some(TextCell::Static(Some(power)))
+        }
+    }
+}
+
+fn marker(power: Power) -> char {
+    power.name().chars().next().unwrap_or(STATIC)
+}
+
+#[derive(Debug)]
+pub enum BoardTextError {
+    /// a row is not as wide as the first one
+    Ragged {
+        row: usize,
+    },
+    UnknownSymbol(char),
+    /// the text is bigger than the board it is being put on
+    DoesNotFit,
+}
+
+impl std::fmt::Display for BoardTextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardTextError::Ragged { row } => write!(f, "row {row} is not as wide as the first"),
+            BoardTextError::UnknownSymbol(symbol) => write!(f, "{symbol:?} is not a board symbol"),
+            BoardTextError::DoesNotFit => write!(f, "text does not fit on the board"),
+        }
+    }
+}
+
+impl std::error::Error for BoardTextError {}
+
+/// a board read from text, cells are from the floor up
+#[derive(Clone, Debug)]
+pub struct BoardText {
+    pub width: i32,
+    pub hight: i32,
+    pub cells: Vec<(IVec2, TextCell)>,
+}
+
+impl BoardText {
+    /// blank lines are skipped and every line is trimmed so boards can be written indented in raw strings
+    pub fn parse(text: &str) -> Result<BoardText, BoardTextError> {
+        let rows = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        let mut cells = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(BoardTextError::Ragged { row: index });
+            }
+            let y = (rows.len() - 1 - index) as i32;
+            for (x, symbol) in row.chars().enumerate() {
+                let cell =
+                    TextCell::from_symbol(symbol).ok_or(BoardTextError::UnknownSymbol(symbol))?;
+                if cell != TextCell::Empty {
+                    cells.push((IVec2::new(x as i32, y), cell));
+                }
+            }
+        }
+        cells.sort_by_key(|(cell, _)| (cell.y, cell.x));
+        Ok(BoardText {
+            width: width as i32,
+            hight: rows.len() as i32,
+            cells,
+        })
+    }
+
+    /// put the blocks on `board`, which should be empty, the active blocks become one shape the player controls
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        player: Entity,
+        board: &mut Board,
+        block_image: &BlockImage,
+        skin: BlockSkin,
+    ) -> Result<(), BoardTextError> {
+        if self.width > board.width() || self.hight > board.hight() {
+            return Err(BoardTextError::DoesNotFit);
+        }
+        let active = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| matches!(cell, TextCell::Active(_)))
+            .collect::<Vec<_>>();
+        let shape = active.first().map(|(center, _)| {
+            let mut shape = Shape {
+                // the blocks are spawned here so spawn_shape has to skip it
+                split: true,
+                center: *center,
+                blocks: active.iter().map(|(cell, _)| cell - center).collect(),
+                color: Color::WHITE,
+                center_of_mass: Vec2::ZERO,
+                pivot: Vec2::ZERO,
+                powers: Vec::new(),
+            };
+            shape.calc_center();
+            commands
+                .spawn((shape, OnBoard(player), PlayerTarget::default()))
+                .id()
+        });
+        for (cell, kind) in self.cells.iter() {
+            let (shape, power, color) = match kind {
+                TextCell::Empty => continue,
+                TextCell::Static(power) => (None, *power, GARBAGE_COLOR),
+                TextCell::Active(power) => (shape, *power, Color::WHITE),
+            };
+            spawn_board_block(
+                commands,
+                player,
+                board,
+                *cell,
+                block_image.sprite(skin, color),
+                power,
+                Block {
+                    shape,
+                    moved: false,
+                    effects: HashSet::with_hasher(FixedHasher),
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// what is needed to write a board out as text
+#[derive(SystemParam)]
+pub struct BoardDump<'w, 's> {
+    blocks: Query<'w, 's, (&'static Block, Has<Lightning>, Has<Bomb>)>,
+    active: Query<'w, 's, (), With<PlayerTarget>>,
+}
+
+impl BoardDump<'_, '_> {
+    pub fn cell(&self, board: &Board, cell: IVec2) -> Option<TextCell> {
+        let BlockState::Contains(entity) = board.get(cell) else {
+            return Some(TextCell::Empty);
+        };
+        let (block, lightning, bomb) = self.blocks.get(entity).ok()?;
+        let power = match (lightning, bomb) {
+            (true, _) => Some(Power::Lightning),
+            (_, true) => Some(Power::Bomb),
+            _ => None,
+        };
+        if block.shape.is_some_and(|shape| self.active.contains(shape)) {
+            Some(TextCell::Active(power))
+        } else {
+            Some(TextCell::Static(power))
+        }
+    }
+
+    /// the board as `BoardText::parse` reads it, top row first
+    pub fn dump(&self, board: &Board) -> String {
+        let mut text = String::new();
+        for y in (0..board.hight()).rev() {
+            for x in 0..board.width() {
+                let symbol = self
+                    .cell(board, IVec2::new(x, y))
+                    .map_or(INVALID, TextCell::symbol);
+                text.push(symbol);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{clear_line, BlocksDestroyed, LineInfo, LinesCleared};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .init_resource::<BlockImage>()
+            .insert_resource(BlockSkin::Classic)
+            .add_event::<BlocksDestroyed>()
+            .add_event::<LinesCleared>();
+        app
+    }
+
+    fn spawn_text(
+        In(text): In<BoardText>,
+        mut commands: Commands,
+        mut boards: Query<(Entity, &mut Board)>,
+        block_image: Res<BlockImage>,
+        skin: Res<BlockSkin>,
+    ) -> Result<(), BoardTextError> {
+        let (entity, mut board) = boards.single_mut().expect("one board");
+        text.spawn(&mut commands, entity, &mut board, &block_image, *skin)
+    }
+
+    fn dump_board(boards: Query<&Board>, dump: BoardDump) -> String {
+        dump.dump(boards.single().expect("one board"))
+    }
+
+    /// a board just big enough for `text` with its blocks spawned
+    fn load(text: &str) -> App {
+        let text = BoardText::parse(text).unwrap();
+        let mut app = app();
+        let world = app.world_mut();
+        world.spawn((Board::new(text.width, text.hight), LineInfo::default()));
+        world
+            .run_system_cached_with(spawn_text, text)
+            .expect("spawn_text only takes world params")
+            .unwrap();
+        app
+    }
+
+    fn dump(app: &mut App) -> String {
+        app.world_mut()
+            .run_system_cached(dump_board)
+            .expect("dump_board only takes world params")
+    }
+
+    /// the text the same way `BoardDump::dump` writes it
+    fn unindent(text: &str) -> String {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| format!("{line}\n"))
+            .collect()
+    }
+
+    #[test]
+    fn parse_reads_from_the_floor_up() {
+        let text = BoardText::parse(
+            r"
+            .@.
+            #b.
+            ",
+        )
+        .unwrap();
+        assert_eq!((text.width, text.hight), (3, 2));
+        assert_eq!(
+            text.cells,
+            vec![
+                (IVec2::new(0, 0), TextCell::Static(None)),
+                (IVec2::new(1, 0), TextCell::Static(Some(Power::Bomb))),
+                (IVec2::new(1, 1), TextCell::Active(None)),
+            ]
+        );
+    }
+
+    #[test]
+    fn dump_writes_what_parse_read() {
+        let text = r"
+            ..@@..
+            ...@L.
+            #..l.#
+            ##b###
+        ";
+        assert_eq!(dump(&mut load(text)), unindent(text));
+    }
+
+    #[test]
+    fn ragged_rows_are_rejected() {
+        let result = BoardText::parse(
+            r"
+            ....
+            ...
+            ....
+            ",
+        );
+        assert!(matches!(result, Err(BoardTextError::Ragged { row: 1 })));
+    }
+
+    #[test]
+    fn unknown_symbols_are_rejected() {
+        let result = BoardText::parse(
+            r"
+            ..
+            #x
+            ",
+        );
+        assert!(matches!(result, Err(BoardTextError::UnknownSymbol('x'))));
+        assert!(matches!(
+            BoardText::parse(&INVALID.to_string()),
+            Err(BoardTextError::UnknownSymbol(INVALID))
+        ));
+    }
+
+    #[test]
+    fn full_rows_are_cleared() {
+        let mut app = load(
+            r"
+            ..@.
+            .@@@
+            #.##
+            ####
+            ",
+        );
+        app.world_mut()
+            .run_system_cached(clear_line)
+            .expect("clear_line only takes world params");
+        assert_eq!(
+            dump(&mut app),
+            unindent(
+                r"
+                ..@.
+                .@@@
+                #.##
+                ....
+                "
+            )
+        );
+        let world = app.world_mut();
+        let chains = world
+            .query::<&LineInfo>()
+            .iter(world)
+            .map(|info| info.chain)
+            .collect::<Vec<_>>();
+        assert_eq!(chains, vec![1]);
+    }
+}
@@ -0,0 +1,276 @@
+use bevy::prelude::*;
+
+use crate::{
+    ai::{find_placement, step_towards, without_shape, Placement},
+    board::{BlockState, Board, GameClock, OnBoard, Shape, ToppedOut},
+    deck::{CurrentDeck, PlayerInputs, PlayerTarget, ReadInputs, TickInputs},
+    player::Player,
+    prelude::*,
+};
+
+mod protocol;
+
+use protocol::BotClient;
+pub use protocol::{BotCommand, BotEvent, BotListener, BotShape};
+
+/// ticks between each move while carrying out a placement
+const BOT_MOVE_TICKS: u32 = 1;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, start_bot_server)
+        .add_systems(
+            Update,
+            (accept_bot, read_commands, flush_events)
+                .chain()
+                .run_if(resource_exists::<BotServer>),
+        )
+        .add_systems(
+            FixedFirst,
+            bot_inputs
+                .in_set(ReadInputs)
+                .after(crate::deck::take_inputs)
+                .run_if(bot_connected.and(in_state(GameState::Playing))),
+        )
+        .add_systems(
+            FixedLast,
+            (report_spawns, report_game_over)
+                .run_if(bot_connected.and(in_state(GameState::Playing))),
+        );
+}
+
+/// bots connect here and play as player one, set with `--bot <address>` or `--bot unix:<path>`
+#[derive(Resource)]
+pub struct BotServer {
+    listener: BotListener,
+    client: Option<BotClient>,
+    /// the placement being worked towards and the shape it is for once that shape is known
+    plan: Option<(Option<Entity>, BotCommand)>,
+    /// inputs held by the last raw input command
+    held: Vec<PlayerInputs>,
+    wait: u32,
+}
+
+impl BotServer {
+    /// tell the bot something, dropping it if it has gone
+    fn send(&mut self, event: &BotEvent) {
+        let Some(client) = self.client.as_mut() else {
+            return;
+        };
+        if let Err(e) = client.send(event) {
+            warn!("Bot disconnected: {e}");
+            self.disconnect();
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.client = None;
+        self.plan = None;
+        self.held.clear();
+    }
+}
+
+fn bot_connected(server: Option<Res<BotServer>>) -> bool {
+    server.is_some_and(|server| server.client.is_some())
+}
+
+fn start_bot_server(mut commands: Commands) {
+    let mut args = std::env::args().skip_while(|arg| arg != "--bot").skip(1);
+    let Some(address) = args.next() else {
+        return;
+    };
+    match BotListener::bind(&address) {
+        Ok(listener) => {
+            info!("Waiting for a bot on {address}");
+            commands.insert_resource(BotServer {
+                listener,
+                client: None,
+                plan: None,
+                held: Vec::new(),
+                wait: 0,
+            });
+        }
+        Err(e) => error!("Failed to listen for bots on {address}: {e}"),
+    }
+}
+
+fn accept_bot(mut server: ResMut<BotServer>) {
+    if server.client.is_some() {
+        return;
+    }
+    match server.listener.accept() {
+        Ok(Some(client)) => {
+            info!("Bot connected");
+            server.client = Some(client);
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to accept bot: {e}"),
+    }
+}
+
+fn read_commands(mut server: ResMut<BotServer>) {
+    let Some(client) = server.client.as_mut() else {
+        return;
+    };
+    let lines = match client.lines() {
+        Ok(lines) => lines,
+        Err(e) => {
+            info!("Bot disconnected: {e}");
+            server.disconnect();
+            return;
+        }
+    };
+    for line in lines.iter().filter(|line| !line.is_empty()) {
+        match serde_json::from_str::<BotCommand>(line) {
+            Ok(BotCommand::Input { pressed }) => {
+                server.plan = None;
+                server.held = pressed;
+            }
+            Ok(place) => {
+                server.held.clear();
+                server.plan = Some((None, place));
+            }
+            Err(e) => server.send(&BotEvent::Error {
+                message: format!("could not read {line:?}: {e}"),
+            }),
+        }
+    }
+}
+
+/// keep writing events a slow bot has not taken yet
+fn flush_events(mut server: ResMut<BotServer>) {
+    let Some(client) = server.client.as_mut() else {
+        return;
+    };
+    if let Err(e) = client.flush() {
+        warn!("Bot disconnected: {e}");
+        server.disconnect();
+    }
+}
+
+/// where `place` puts `shape`, `None` if it can't get there
+fn resolve_placement(place: &BotCommand, shape: &Shape, board: &Board) -> Option<Placement> {
+    let BotCommand::Place { x, y, rotation } = place else {
+        return None;
+    };
+    find_placement(shape, board, *x, *y, *rotation)
+}
+
+fn bot_inputs(
+    mut server: ResMut<BotServer>,
+    mut players: Query<(Entity, &Player, &Board, &mut TickInputs)>,
+    target: Query<(Entity, &Shape, &OnBoard), With<PlayerTarget>>,
+) {
+    let Some((entity, _, board, mut inputs)) = players
+        .iter_mut()
+        .find(|(_, player, _, _)| player.index == 0)
+    else {
+        return;
+    };
+    let last = *inputs;
+    let server = &mut *server;
+    if server.plan.is_none() {
+        *inputs = TickInputs::from_held(&server.held, last);
+        return;
+    }
+    *inputs = TickInputs::default();
+    let Some((shape_entity, shape)) = target
+        .iter()
+        .find(|(_, _, on_board)| on_board.0 == entity)
+        .map(|(shape_entity, shape, _)| (shape_entity, shape))
+    else {
+        return;
+    };
+    let Some((planned_for, place)) = server.plan.take() else {
+        return;
+    };
+    // the shape the plan was for has locked
+    if planned_for.is_some_and(|planned_for| planned_for != shape_entity) {
+        return;
+    }
+    if server.wait > 0 {
+        server.wait -= 1;
+        server.plan = Some((Some(shape_entity), place));
+        return;
+    }
+    let board = without_shape(board, shape);
+    let Some(placement) = resolve_placement(&place, shape, &board) else {
+        server.send(&BotEvent::Error {
+            message: "the shape can't reach that placement".into(),
+        });
+        return;
+    };
+    server.plan = Some((Some(shape_entity), place));
+    let Some(step) = step_towards(shape, &board, &placement) else {
+        return;
+    };
+    server.wait = BOT_MOVE_TICKS;
+    *inputs = step;
+}
+
+fn bot_shape(shape: &Shape) -> BotShape {
+    BotShape {
+        center: shape.center.into(),
+        blocks: shape.blocks.iter().map(|block| (*block).into()).collect(),
+        powers: shape
+            .powers
+            .iter()
+            .map(|(block, power)| (*block, power.name().to_string()))
+            .collect(),
+    }
+}
+
+fn report_spawns(
+    mut server: ResMut<BotServer>,
+    clock: Res<GameClock>,
+    players: Query<(Entity, &Player, &Board, &CurrentDeck, &Score)>,
+    spawned: Query<(&Shape, &OnBoard), Added<PlayerTarget>>,
+) {
+    for (shape, on_board) in &spawned {
+        let Ok((_, player, board, deck, score)) = players.get(on_board.0) else {
+            continue;
+        };
+        if player.index != 0 {
+            continue;
+        }
+        let active = shape
+            .blocks
+            .iter()
+            .map(|block| shape.center + block)
+            .collect::<Vec<_>>();
+        let cells = (0..board.hight())
+            .map(|y| {
+                (0..board.width())
+                    .map(|x| {
+                        let cell = IVec2::new(x, y);
+                        match board.get(cell) {
+                            BlockState::Contains(_) if active.contains(&cell) => 2,
+                            BlockState::Contains(_) => 1,
+                            _ => 0,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        server.send(&BotEvent::Spawn {
+            tick: clock.tick,
+            width: board.width(),
+            hight: board.hight(),
+            cells,
+            shape: bot_shape(shape),
+            next: deck.upcoming().map(bot_shape).collect(),
+            score: score.0,
+        });
+    }
+}
+
+fn report_game_over(
+    mut server: ResMut<BotServer>,
+    mut events: EventReader<ToppedOut>,
+    players: Query<(&Player, &Score)>,
+) {
+    for ToppedOut { board } in events.read() {
+        if let Ok((Player { index: 0 }, score)) = players.get(*board) {
+            server.send(&BotEvent::GameOver { score: score.0 });
+        }
+    }
+}
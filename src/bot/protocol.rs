@@ -0,0 +1,205 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::deck::PlayerInputs;
+
+/// bytes a bot can fall behind on reading before it is dropped
+const MAX_BACKLOG: usize = 1 << 20;
+
+/// sent to the bot, one JSON object per line
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotEvent {
+    /// a new shape is ready to be placed
+    Spawn {
+        tick: u64,
+        width: i32,
+        hight: i32,
+        /// rows from the bottom up, 0 is empty, 1 a block and 2 part of the new shape
+        cells: Vec<Vec<u8>>,
+        shape: BotShape,
+        /// shapes still to come this bag, the next one first
+        next: Vec<BotShape>,
+        score: i32,
+    },
+    GameOver {
+        score: i32,
+    },
+    /// a command could not be read or carried out
+    Error {
+        message: String,
+    },
+}
+
+#[derive(serde::Serialize)]
+pub struct BotShape {
+    /// where the shape is on the board, blocks are offsets from here
+    pub center: [i32; 2],
+    pub blocks: Vec<[i32; 2]>,
+    /// block index and the name of its power
+    pub powers: Vec<(usize, String)>,
+}
+
+/// read from the bot, one JSON object per line
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotCommand {
+    /// move the current shape to column `x` turned `rotation` quarter turns and drop it there
+    /// `y` picks a spot under an overhang, otherwise the shape drops as far as it can
+    Place {
+        x: i32,
+        #[serde(default)]
+        y: Option<i32>,
+        #[serde(default)]
+        rotation: u32,
+    },
+    /// hold down exactly these inputs until the next command
+    Input { pressed: Vec<PlayerInputs> },
+}
+
+/// where bots connect, `unix:` followed by a path for a unix socket or an address for tcp
+pub enum BotListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl BotListener {
+    pub fn bind(address: &str) -> std::io::Result<Self> {
+        let listener = match address.strip_prefix("unix:") {
+            #[cfg(unix)]
+            Some(path) => {
+                // a socket left behind by an earlier run would stop the bind
+                let _ = std::fs::remove_file(path);
+                let listener = UnixListener::bind(path)?;
+                listener.set_nonblocking(true)?;
+                BotListener::Unix(listener)
+            }
+            #[cfg(not(unix))]
+            Some(_) => {
+                return Err(std::io::Error::new(
+                    ErrorKind::Unsupported,
+                    "unix sockets are not supported here",
+                ))
+            }
+            None => {
+                let listener = TcpListener::bind(address)?;
+                listener.set_nonblocking(true)?;
+                BotListener::Tcp(listener)
+            }
+        };
+        Ok(listener)
+    }
+
+    /// a bot that has connected since the last call
+    pub fn accept(&self) -> std::io::Result<Option<BotClient>> {
+        let stream = match self {
+            BotListener::Tcp(listener) => listener.accept().map(|(stream, _)| {
+                let _ = stream.set_nodelay(true);
+                BotStream::Tcp(stream)
+            }),
+            #[cfg(unix)]
+            BotListener::Unix(listener) => {
+                listener.accept().map(|(stream, _)| BotStream::Unix(stream))
+            }
+        };
+        match stream {
+            Ok(stream) => {
+                stream.set_nonblocking()?;
+                Ok(Some(BotClient {
+                    stream,
+                    partial: Vec::new(),
+                    outgoing: Vec::new(),
+                }))
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+enum BotStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl BotStream {
+    fn set_nonblocking(&self) -> std::io::Result<()> {
+        match self {
+            BotStream::Tcp(stream) => stream.set_nonblocking(true),
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream.set_nonblocking(true),
+        }
+    }
+
+    fn stream(&mut self) -> &mut dyn ReadWrite {
+        match self {
+            BotStream::Tcp(stream) => stream,
+            #[cfg(unix)]
+            BotStream::Unix(stream) => stream,
+        }
+    }
+}
+
+trait ReadWrite: Read + Write {}
+
+impl<T: Read + Write> ReadWrite for T {}
+
+/// a connected bot
+pub struct BotClient {
+    stream: BotStream,
+    /// the start of a line that has not fully arrived yet
+    partial: Vec<u8>,
+    /// events the socket has not taken yet
+    outgoing: Vec<u8>,
+}
+
+impl BotClient {
+    /// queue an event, as much of it as the socket will take is written straight away
+    pub fn send(&mut self, event: &BotEvent) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.outgoing, event)?;
+        self.outgoing.push(b'\n');
+        self.flush()
+    }
+
+    /// write queued events without waiting, `Err` once the bot has gone or stopped reading
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        let stream = self.stream.stream();
+        let mut written = 0;
+        while written < self.outgoing.len() {
+            match stream.write(&self.outgoing[written..]) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        self.outgoing.drain(..written);
+        if self.outgoing.len() > MAX_BACKLOG {
+            return Err(std::io::Error::other("the bot stopped reading"));
+        }
+        Ok(())
+    }
+
+    /// every full line that has arrived, `Err` once the bot has gone
+    pub fn lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut buf = [0; 1024];
+        loop {
+            match self.stream.stream().read(&mut buf) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.partial.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        let mut lines = Vec::new();
+        while let Some(end) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line = self.partial.drain(..=end).collect::<Vec<_>>();
+            lines.push(String::from_utf8_lossy(&line).trim().to_string());
+        }
+        Ok(lines)
+    }
+}
@@ -1,19 +1,37 @@
 use bevy::prelude::*;
+use bevy_pkv::PkvStore;
 use leafwing_input_manager::prelude::*;
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 
 use crate::{
     board::{self, Shape},
+    prelude::*,
     GameState,
 };
 
-#[derive(Resource)]
+mod asset;
+mod validate;
+
+pub(crate) use asset::ShapeFile;
+pub use asset::{DeckAsset, DeckLibrary};
+pub use validate::{DeckProblem, DeckReport};
+
+#[derive(Resource, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Deck {
     shapes: Vec<Shape>,
+    /// how many copies of each shape go into each bag, 0.5 is one every other bag
+    weights: Vec<f32>,
 }
 
 impl FromWorld for Deck {
     fn from_world(_world: &mut World) -> Self {
+        Deck::classic()
+    }
+}
+
+impl Deck {
+    /// the seven standard tetrominoes
+    pub fn classic() -> Self {
         let mut deck = Deck {
             shapes: vec![
                 Shape {
@@ -27,6 +45,8 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::LIGHT_BLUE.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::new(0.5, -0.5),
+                    powers: Vec::new(),
                 },
                 Shape {
                     split: false,
@@ -39,6 +59,8 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::RED.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::ZERO,
+                    powers: Vec::new(),
                 },
                 Shape {
                     split: false,
@@ -51,6 +73,8 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::YELLOW.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::new(-0.5, -0.5),
+                    powers: Vec::new(),
                 },
                 Shape {
                     split: false,
@@ -63,6 +87,8 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::LIGHT_GREEN.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::ZERO,
+                    powers: Vec::new(),
                 },
                 Shape {
                     split: false,
@@ -75,6 +101,8 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::PURPLE.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::ZERO,
+                    powers: Vec::new(),
                 },
                 Shape {
                     split: false,
@@ -87,6 +115,8 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::DARK_BLUE.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::ZERO,
+                    powers: Vec::new(),
                 },
                 Shape {
                     split: false,
@@ -99,8 +129,11 @@ impl FromWorld for Deck {
                     ],
                     color: bevy::color::palettes::css::ORANGE.into(),
                     center_of_mass: Vec2::ZERO,
+                    pivot: Vec2::ZERO,
+                    powers: Vec::new(),
                 },
             ],
+            weights: vec![1.; 7],
         };
         // let mut deck = Deck {
         //     shapes: vec![Shape {
@@ -134,33 +167,249 @@ impl FromWorld for Deck {
         }
         deck
     }
+
+    pub fn from_shapes(shapes: Vec<Shape>) -> Self {
+        let weights = vec![1.; shapes.len()];
+        Deck::from_weighted(shapes, weights)
+    }
+
+    pub fn from_weighted(mut shapes: Vec<Shape>, weights: Vec<f32>) -> Self {
+        debug_assert_eq!(shapes.len(), weights.len());
+        for shape in shapes.iter_mut() {
+            shape.calc_center();
+        }
+        Deck { shapes, weights }
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    pub fn shape_mut(&mut self, index: usize) -> Option<&mut Shape> {
+        self.shapes.get_mut(index)
+    }
+
+    pub fn weight(&self, index: usize) -> f32 {
+        self.weights.get(index).copied().unwrap_or(0.)
+    }
+
+    pub fn set_weight(&mut self, index: usize, weight: f32) {
+        if let Some(old) = self.weights.get_mut(index) {
+            *old = weight;
+        }
+    }
+
+    pub fn add(&mut self, mut shape: Shape) {
+        shape.calc_center();
+        self.shapes.push(shape);
+        self.weights.push(1.);
+    }
+
+    /// remove a shape from the deck, the last shape can not be removed
+    pub fn remove(&mut self, index: usize) -> Option<Shape> {
+        if self.shapes.len() <= 1 || index >= self.shapes.len() {
+            return None;
+        }
+        self.weights.remove(index);
+        Some(self.shapes.remove(index))
+    }
 }
 
-#[derive(Resource)]
-pub struct CurrentDeck {
-    shapes: Vec<Shape>,
+/// shapes the player has made in the shape editor
+#[derive(Resource, Deref, DerefMut)]
+pub struct CustomDeck(pub Deck);
+
+impl FromWorld for CustomDeck {
+    fn from_world(world: &mut World) -> Self {
+        let store = world.resource::<PkvStore>();
+        if let Ok(deck) = store.get(DataKeys::CustomDeck) {
+            CustomDeck(deck)
+        } else {
+            CustomDeck(Deck::from_shapes(Vec::new()))
+        }
+    }
 }
 
-impl FromWorld for CurrentDeck {
+/// the name of the deck that is played, `CUSTOM_DECK` for the shape editor deck
+#[derive(Resource)]
+pub struct SelectedDeck(pub String);
+
+/// name of the deck made in the shape editor
+pub const CUSTOM_DECK: &str = "Custom";
+
+impl FromWorld for SelectedDeck {
     fn from_world(world: &mut World) -> Self {
-        let deck = world.resource::<Deck>();
-        let mut deck = CurrentDeck {
-            shapes: deck.shapes.to_vec(),
+        let store = world.resource::<PkvStore>();
+        if let Ok(name) = store.get(DataKeys::SelectedDeck) {
+            SelectedDeck(name)
+        } else {
+            SelectedDeck("Classic".into())
+        }
+    }
+}
+
+fn save_selected_deck(mut store: ResMut<PkvStore>, selected: Res<SelectedDeck>) {
+    if let Err(e) = store.set(DataKeys::SelectedDeck, &selected.0) {
+        error!("Failed to save selected deck: {e:?}");
+    };
+}
+
+/// every deck that can be picked by name, the custom deck comes last if it has any shapes
+pub fn deck_list(decks: &Assets<DeckAsset>, custom: &CustomDeck) -> Vec<(String, Deck)> {
+    let mut list = decks
+        .iter()
+        .map(|(_, asset)| (asset.name.clone(), asset.deck.clone()))
+        .collect::<Vec<_>>();
+    list.sort_by(|(a, _), (b, _)| a.cmp(b));
+    if !custom.shapes().is_empty() {
+        list.push((CUSTOM_DECK.into(), custom.0.clone()));
+    }
+    list
+}
+
+/// find a deck by name and check it can be played on `board`
+pub fn find_deck(
+    name: &str,
+    decks: &Assets<DeckAsset>,
+    custom: &CustomDeck,
+    board: &board::Board,
+) -> Result<Deck, Vec<DeckProblem>> {
+    let deck = if name == CUSTOM_DECK {
+        custom.0.clone()
+    } else {
+        let Some((_, asset)) = decks.iter().find(|(_, asset)| asset.name == name) else {
+            return Err(vec![DeckProblem::NotFound(name.into())]);
         };
-        deck.shapes.shuffle(&mut rand::rng());
-        deck
+        if !asset.problems.is_empty() {
+            return Err(asset.problems.clone());
+        }
+        asset.deck.clone()
+    };
+    let problems = deck.validate(board);
+    if problems.is_empty() {
+        Ok(deck)
+    } else {
+        Err(problems)
     }
 }
 
+/// the custom deck is not an asset so it has to be picked up when the game starts
+fn apply_custom_deck(selected: Res<SelectedDeck>, custom: Res<CustomDeck>, mut deck: ResMut<Deck>) {
+    if selected.0 != CUSTOM_DECK || !custom.validate(&board::Board::default()).is_empty() {
+        return;
+    }
+    *deck = custom.0.clone();
+}
+
+fn save_custom_deck(mut store: ResMut<PkvStore>, deck: Res<CustomDeck>) {
+    if let Err(e) = store.set(DataKeys::CustomDeck, &deck.0) {
+        error!("Failed to save custom deck: {e:?}");
+    };
+}
+
+/// the shapes a player has coming up, every player draws from their own bag
+#[derive(Component, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CurrentDeck {
+    shapes: Vec<Shape>,
+    /// weight left over from earlier bags for each shape in the deck
+    credit: Vec<f32>,
+    /// the shapes were set up front and no more bags get added once they run out
+    fixed: bool,
+}
+
 impl CurrentDeck {
-    pub fn next(&mut self) -> Shape {
-        self.shapes.pop().expect("Always at least one shape")
+    /// a fresh shuffle of `deck`, shuffled with `rng` so the order can be played again
+    pub fn new(deck: &Deck, rng: &mut impl Rng) -> Self {
+        let mut current = CurrentDeck {
+            shapes: Vec::new(),
+            credit: vec![0.; deck.shapes.len()],
+            fixed: false,
+        };
+        current.refill(deck, rng);
+        current
+    }
+
+    /// exactly these shapes in this order and nothing after them
+    pub fn fixed(shapes: Vec<Shape>) -> Self {
+        let mut shapes = shapes
+            .into_iter()
+            .map(|mut shape| {
+                shape.calc_center();
+                shape
+            })
+            .collect::<Vec<_>>();
+        shapes.reverse();
+        CurrentDeck {
+            shapes,
+            credit: Vec::new(),
+            fixed: true,
+        }
+    }
+
+    /// deal `shapes` in order before anything already in the bag
+    pub fn deal_first(&mut self, shapes: Vec<Shape>) {
+        for mut shape in shapes.into_iter().rev() {
+            shape.calc_center();
+            self.shapes.push(shape);
+        }
+    }
+
+    /// the shapes left in the bag, the next one first
+    pub fn upcoming(&self) -> impl Iterator<Item = &Shape> {
+        self.shapes.iter().rev()
+    }
+
+    /// add a shuffled bag with each shape in it as many times as its weight allows
+    /// weights below one build up over a few bags until the shape gets in
+    fn refill(&mut self, deck: &Deck, rng: &mut impl Rng) {
+        self.credit.resize(deck.shapes.len(), 0.);
+        let total = deck
+            .weights
+            .iter()
+            .map(|weight| weight.max(0.))
+            .sum::<f32>();
+        if total <= 0. || !total.is_finite() {
+            return;
+        }
+        let mut bag = Vec::new();
+        while bag.is_empty() {
+            // skip the rounds where nothing would reach a whole copy, tiny weights would take forever
+            let rounds = (0..deck.shapes.len())
+                .filter(|index| deck.weight(*index) > 0.)
+                .map(|index| {
+                    ((1. - self.credit[index]) / deck.weight(index))
+                        .ceil()
+                        .max(1.)
+                })
+                .fold(f32::INFINITY, f32::min);
+            for (index, shape) in deck.shapes.iter().enumerate() {
+                self.credit[index] += deck.weight(index).max(0.) * rounds;
+                while self.credit[index] >= 1. {
+                    self.credit[index] -= 1.;
+                    bag.push(shape.clone());
+                }
+            }
+        }
+        bag.shuffle(rng);
+        // shapes are popped off the end so the new bag goes under what is left
+        self.shapes.splice(0..0, bag);
+    }
+
+    /// the next shape, only `None` once a fixed deck has run out
+    pub fn draw(&mut self) -> Option<Shape> {
+        self.shapes.pop()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
     }
 }
 
-fn refill_deck(mut current: ResMut<CurrentDeck>, deck: Res<Deck>) {
-    for shape in deck.shapes.iter() {
-        current.shapes.push(shape.clone());
+fn refill_deck(mut decks: Query<&mut CurrentDeck>, deck: Res<Deck>, mut rng: ResMut<GameRng>) {
+    for mut current in &mut decks {
+        if current.shapes.is_empty() && !current.fixed {
+            current.refill(&deck, &mut **rng);
+        }
     }
 }
 
@@ -168,6 +417,18 @@ fn refill_deck(mut current: ResMut<CurrentDeck>, deck: Res<Deck>) {
 pub struct PlayerTarget {
     pub last_y: u8,
     pub moved: bool,
+    /// the last thing that moved the shape was a rotation, spins need this when it locks
+    pub rotated: bool,
+}
+
+impl PlayerTarget {
+    /// the shape was rotated into a spot it can't slide out of
+    pub fn spun(&self, shape: &Shape, board: &board::Board) -> bool {
+        self.rotated
+            && ![IVec2::X, IVec2::NEG_X, IVec2::Y]
+                .into_iter()
+                .any(|offset| shape.can_translate(board, offset))
+    }
 }
 
 impl Default for PlayerTarget {
@@ -175,87 +436,221 @@ impl Default for PlayerTarget {
         PlayerTarget {
             last_y: 0,
             moved: true,
+            rotated: false,
         }
     }
 }
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, (player_moves,).run_if(in_state(GameState::Playing)))
-        .add_systems(
-            FixedLast,
-            remove_player_target.run_if(in_state(GameState::Playing)),
-        )
-        .add_systems(
-            First,
-            refill_deck.run_if(|deck: Res<CurrentDeck>| deck.shapes.is_empty()),
-        )
-        .add_systems(FixedFirst, clear_moved)
-        .init_resource::<Deck>()
-        .init_resource::<CurrentDeck>()
-        .insert_resource(ActionState::<PlayerInputs>::default())
-        .insert_resource(InputMap::new([
-            (PlayerInputs::MoveLeft, KeyCode::KeyA),
-            (PlayerInputs::MoveRight, KeyCode::KeyD),
-            (PlayerInputs::MoveDown, KeyCode::KeyS),
-            (PlayerInputs::Rotate, KeyCode::KeyW),
-            (PlayerInputs::MoveLeft, KeyCode::ArrowLeft),
-            (PlayerInputs::MoveRight, KeyCode::ArrowRight),
-            (PlayerInputs::MoveDown, KeyCode::ArrowDown),
-            (PlayerInputs::Rotate, KeyCode::ArrowUp),
-        ]));
+    app.add_systems(
+        PreUpdate,
+        latch_inputs.after(leafwing_input_manager::plugin::InputManagerSystem::Update),
+    )
+    .add_systems(FixedFirst, take_inputs.in_set(ReadInputs))
+    .add_systems(
+        FixedUpdate,
+        player_moves
+            .before(board::apply_gravity)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        FixedLast,
+        remove_player_target.run_if(in_state(GameState::Playing).and(board::on_step)),
+    )
+    .add_systems(FixedUpdate, refill_deck.before(board::spawn_next))
+    .add_systems(
+        FixedFirst,
+        clear_moved
+            .after(board::advance_clock)
+            .run_if(board::on_step),
+    )
+    .init_resource::<Deck>()
+    .init_resource::<DeckReport>()
+    .init_resource::<CustomDeck>()
+    .init_resource::<SelectedDeck>()
+    .add_systems(Startup, apply_custom_deck)
+    .add_systems(
+        Update,
+        save_selected_deck.run_if(resource_changed::<SelectedDeck>),
+    )
+    .add_systems(
+        Update,
+        save_custom_deck.run_if(resource_changed::<CustomDeck>),
+    )
+    .add_plugins(asset::plugin)
+    .insert_resource(ActionState::<PlayerInputs>::default())
+    .insert_resource(InputMap::new([
+        (PlayerInputs::MoveLeft, KeyCode::KeyA),
+        (PlayerInputs::MoveRight, KeyCode::KeyD),
+        (PlayerInputs::MoveDown, KeyCode::KeyS),
+        (PlayerInputs::Rotate, KeyCode::KeyW),
+        (PlayerInputs::MoveLeft, KeyCode::ArrowLeft),
+        (PlayerInputs::MoveRight, KeyCode::ArrowRight),
+        (PlayerInputs::MoveDown, KeyCode::ArrowDown),
+        (PlayerInputs::Rotate, KeyCode::ArrowUp),
+        (PlayerInputs::Undo, KeyCode::KeyZ),
+        (PlayerInputs::Redo, KeyCode::KeyY),
+    ]));
 }
 
 fn player_moves(
-    time: Res<Time>,
-    settings: Res<ActionState<PlayerInputs>>,
-    mut target: Query<(Entity, &mut Shape, &mut PlayerTarget)>,
-    mut board: ResMut<board::Board>,
-    mut last: Local<f32>,
+    mut players: Query<(&TickInputs, &mut InputLatch, &mut board::Board)>,
+    mut target: Query<(Entity, &mut Shape, &mut PlayerTarget, &board::OnBoard)>,
     mut commands: Commands,
 ) {
-    for (entity, mut shape, mut target) in &mut target {
-        if settings.just_pressed(&PlayerInputs::MoveLeft) {
-            shape.translate(&mut board, IVec2::NEG_X);
-            *last = 0.;
+    for (entity, mut shape, mut target, on_board) in &mut target {
+        let Ok((inputs, mut latch, mut board)) = players.get_mut(on_board.0) else {
+            continue;
+        };
+        let held = &mut latch.repeat;
+        if inputs.just_pressed(PlayerInputs::MoveLeft) {
+            if shape.translate(&mut board, IVec2::NEG_X) {
+                target.rotated = false;
+            }
+            *held = 0;
             target.moved = true;
         }
-        if settings.just_pressed(&PlayerInputs::MoveRight) {
-            shape.translate(&mut board, IVec2::X);
-            *last = 0.;
+        if inputs.just_pressed(PlayerInputs::MoveRight) {
+            if shape.translate(&mut board, IVec2::X) {
+                target.rotated = false;
+            }
+            *held = 0;
             target.moved = true;
         }
-        if settings.just_pressed(&PlayerInputs::Rotate) {
-            shape.rotate(&mut board);
-            *last = 0.;
+        if inputs.just_pressed(PlayerInputs::Rotate) {
+            if shape.rotate(&mut board) {
+                target.rotated = true;
+            }
+            *held = 0;
             target.moved = true;
         }
-        if settings.pressed(&PlayerInputs::MoveDown) {
+        if inputs.pressed(PlayerInputs::MoveDown) {
             if shape.translate(&mut board, IVec2::NEG_Y) {
                 target.last_y = 0;
                 target.moved = true;
+                target.rotated = false;
             } else {
                 commands.entity(entity).remove::<PlayerTarget>();
             }
         }
-        if !settings.get_pressed().is_empty() {
-            *last += time.delta_secs();
+        if inputs.any_pressed() {
+            *held += 1;
         }
-        if *last < 0.5 {
-            return;
-        } else if settings.pressed(&PlayerInputs::MoveLeft) {
-            shape.translate(&mut board, IVec2::NEG_X);
-            *last = 0.;
-        } else if settings.pressed(&PlayerInputs::MoveRight) {
-            shape.translate(&mut board, IVec2::X);
-            *last = 0.;
-        } else if settings.pressed(&PlayerInputs::Rotate) {
-            shape.rotate(&mut board);
-            *last = 0.;
+        if *held < REPEAT_TICKS {
+            continue;
+        } else if inputs.pressed(PlayerInputs::MoveLeft) {
+            if shape.translate(&mut board, IVec2::NEG_X) {
+                target.rotated = false;
+            }
+            *held = 0;
+        } else if inputs.pressed(PlayerInputs::MoveRight) {
+            if shape.translate(&mut board, IVec2::X) {
+                target.rotated = false;
+            }
+            *held = 0;
+        } else if inputs.pressed(PlayerInputs::Rotate) {
+            if shape.rotate(&mut board) {
+                target.rotated = true;
+            }
+            *held = 0;
         }
         target.moved = true;
     }
 }
 
+/// ticks an input has to be held before it repeats
+const REPEAT_TICKS: u32 = 15;
+
+/// the inputs a players shape follows this tick, read from `ActionState` or a replay
+#[derive(
+    Component, Default, Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize,
+)]
+#[require(InputLatch)]
+pub struct TickInputs {
+    pressed: u8,
+    just_pressed: u8,
+}
+
+impl TickInputs {
+    fn bit(input: PlayerInputs) -> u8 {
+        1 << input as u8
+    }
+
+    pub fn pressed(&self, input: PlayerInputs) -> bool {
+        self.pressed & Self::bit(input) != 0
+    }
+
+    pub fn just_pressed(&self, input: PlayerInputs) -> bool {
+        self.just_pressed & Self::bit(input) != 0
+    }
+
+    pub fn any_pressed(&self) -> bool {
+        self.pressed != 0
+    }
+
+    /// `input` went down this tick
+    pub fn tap(input: PlayerInputs) -> Self {
+        TickInputs {
+            pressed: Self::bit(input),
+            just_pressed: Self::bit(input),
+        }
+    }
+
+    /// `input` is being held down
+    pub fn hold(input: PlayerInputs) -> Self {
+        TickInputs {
+            pressed: Self::bit(input),
+            just_pressed: 0,
+        }
+    }
+
+    /// `held` are down this tick, anything that was not down on the `last` tick was just pressed
+    pub fn from_held(held: &[PlayerInputs], last: TickInputs) -> Self {
+        let pressed = held.iter().fold(0, |bits, input| bits | Self::bit(*input));
+        TickInputs {
+            pressed,
+            just_pressed: pressed & !last.pressed,
+        }
+    }
+}
+
+/// collects presses between ticks so a tap shorter than a tick is not lost
+#[derive(Component, Default)]
+struct InputLatch {
+    held: u8,
+    tapped: u8,
+    /// ticks the current input has been held, used for repeating moves
+    repeat: u32,
+}
+
+/// systems that fill `TickInputs`, anything replacing the players input runs after this
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadInputs;
+
+fn latch_inputs(mut players: Query<(&ActionState<PlayerInputs>, &mut InputLatch)>) {
+    for (settings, mut latch) in &mut players {
+        latch.held = 0;
+        for input in PlayerInputs::ALL {
+            if settings.pressed(&input) {
+                latch.held |= TickInputs::bit(input);
+            }
+            if settings.just_pressed(&input) {
+                latch.tapped |= TickInputs::bit(input);
+            }
+        }
+    }
+}
+
+pub(crate) fn take_inputs(mut players: Query<(&mut InputLatch, &mut TickInputs)>) {
+    for (mut latch, mut inputs) in &mut players {
+        *inputs = TickInputs {
+            pressed: latch.held | latch.tapped,
+            just_pressed: latch.tapped,
+        };
+        latch.tapped = 0;
+    }
+}
+
 fn clear_moved(mut target: Query<&mut PlayerTarget>) {
     for mut target in &mut target {
         target.last_y += 1;
@@ -274,10 +669,100 @@ fn remove_player_target(
     }
 }
 
-#[derive(leafwing_input_manager::Actionlike, Reflect, Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(
+    leafwing_input_manager::Actionlike,
+    Reflect,
+    Clone,
+    Copy,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub enum PlayerInputs {
     MoveLeft,
     MoveRight,
     MoveDown,
     Rotate,
+    /// take back the last shape placed in practice
+    Undo,
+    /// put back a shape that was taken back in practice
+    Redo,
+}
+
+impl PlayerInputs {
+    /// keys for each side of the keyboard when two people share it
+    pub fn versus_map(index: usize) -> InputMap<PlayerInputs> {
+        if index == 0 {
+            InputMap::new([
+                (PlayerInputs::MoveLeft, KeyCode::KeyA),
+                (PlayerInputs::MoveRight, KeyCode::KeyD),
+                (PlayerInputs::MoveDown, KeyCode::KeyS),
+                (PlayerInputs::Rotate, KeyCode::KeyW),
+            ])
+        } else {
+            InputMap::new([
+                (PlayerInputs::MoveLeft, KeyCode::ArrowLeft),
+                (PlayerInputs::MoveRight, KeyCode::ArrowRight),
+                (PlayerInputs::MoveDown, KeyCode::ArrowDown),
+                (PlayerInputs::Rotate, KeyCode::ArrowUp),
+            ])
+        }
+    }
+
+    /// the inputs that move shapes, these are the ones latched into `TickInputs`
+    pub const ALL: [PlayerInputs; 4] = [
+        PlayerInputs::MoveLeft,
+        PlayerInputs::MoveRight,
+        PlayerInputs::MoveDown,
+        PlayerInputs::Rotate,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_weights_still_fill_a_bag() {
+        let shapes = Deck::classic().shapes()[..2].to_vec();
+        let deck = Deck::from_weighted(shapes, vec![1e-8, 0.]);
+        let mut rng = GameRng::seeded(1);
+        let mut current = CurrentDeck::new(&deck, &mut *rng);
+        assert_eq!(current.shapes.len(), 1);
+        current.draw();
+        current.refill(&deck, &mut *rng);
+        assert_eq!(current.shapes.len(), 1);
+    }
+
+    #[test]
+    fn weights_add_up_over_bags() {
+        let shapes = Deck::classic().shapes()[..2].to_vec();
+        let deck = Deck::from_weighted(shapes, vec![1., 0.25]);
+        let mut rng = GameRng::seeded(1);
+        let mut current = CurrentDeck::new(&deck, &mut *rng);
+        for _ in 0..3 {
+            current.refill(&deck, &mut *rng);
+        }
+        assert_eq!(current.shapes.len(), 5);
+    }
+
+    #[test]
+    fn turned_shapes_are_duplicates() {
+        let s = Deck::classic().shapes()[3].clone();
+        let mut turned = s.clone();
+        turned.blocks = turned
+            .blocks
+            .iter()
+            .map(|block| IVec2::new(-block.y, block.x))
+            .collect();
+        let deck = Deck::from_shapes(vec![s, turned]);
+        let problems = deck.validate(&board::Board::new(10, 20));
+        assert!(matches!(
+            problems[..],
+            [DeckProblem::DuplicateShape { shape: 1, of: 0 }]
+        ));
+    }
 }
@@ -0,0 +1,228 @@
+use bevy::{
+    asset::{io::Reader, AssetLoadFailedEvent, AssetLoader, LoadContext, LoadedFolder},
+    prelude::*,
+};
+
+use super::{Deck, DeckProblem, DeckReport, SelectedDeck};
+use crate::{
+    blocks::Power,
+    board::{Board, Shape},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<DeckAsset>()
+        .init_asset_loader::<DeckLoader>()
+        .init_resource::<DeckLibrary>()
+        .add_systems(Update, (apply_deck_asset, report_failed_deck));
+}
+
+/// a deck loaded from a `.deck.ron` file
+#[derive(Asset, TypePath)]
+pub struct DeckAsset {
+    pub name: String,
+    pub deck: Deck,
+    /// problems found while reading the file, shapes are checked against the board when applied
+    pub problems: Vec<DeckProblem>,
+}
+
+#[derive(serde::Deserialize)]
+struct DeckFile {
+    name: String,
+    shapes: Vec<ShapeFile>,
+}
+
+/// a shape as it is written in deck and puzzle files
+#[derive(serde::Deserialize)]
+pub(crate) struct ShapeFile {
+    blocks: Vec<(i32, i32)>,
+    #[serde(default)]
+    center: (i32, i32),
+    /// point the shape turns around, can be on a cell corner like (0.5, 0.5)
+    #[serde(default)]
+    pivot: (f32, f32),
+    /// hex colour like "#ADD8E6"
+    color: String,
+    /// copies in each bag, below one means it only shows up in some bags
+    #[serde(default = "default_weight")]
+    weight: f32,
+    /// block index and the name of the power it gets
+    #[serde(default)]
+    powers: Vec<(usize, String)>,
+}
+
+fn default_weight() -> f32 {
+    1.
+}
+
+impl ShapeFile {
+    /// the shape this describes, anything that can't be read is added to `problems` as shape `index`
+    pub(crate) fn into_shape(self, index: usize, problems: &mut Vec<DeckProblem>) -> Shape {
+        let color = Srgba::hex(&self.color).unwrap_or_else(|_| {
+            problems.push(DeckProblem::InvalidColor {
+                shape: index,
+                color: self.color.clone(),
+            });
+            Srgba::WHITE
+        });
+        let mut powers = Vec::with_capacity(self.powers.len());
+        for (block, name) in self.powers {
+            let Some(power) = Power::from_name(&name) else {
+                problems.push(DeckProblem::UnknownPower {
+                    shape: index,
+                    power: name,
+                });
+                continue;
+            };
+            powers.push((block, power));
+        }
+        Shape {
+            split: false,
+            center: self.center.into(),
+            blocks: self.blocks.into_iter().map(IVec2::from).collect(),
+            color: color.into(),
+            center_of_mass: Vec2::ZERO,
+            pivot: self.pivot.into(),
+            powers,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DeckLoadError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for DeckLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeckLoadError::Io(e) => write!(f, "failed to read deck: {e}"),
+            DeckLoadError::Ron(e) => write!(f, "failed to parse deck: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DeckLoadError {}
+
+impl From<std::io::Error> for DeckLoadError {
+    fn from(value: std::io::Error) -> Self {
+        DeckLoadError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for DeckLoadError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        DeckLoadError::Ron(value)
+    }
+}
+
+#[derive(Default)]
+struct DeckLoader;
+
+impl AssetLoader for DeckLoader {
+    type Asset = DeckAsset;
+    type Settings = ();
+    type Error = DeckLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<DeckAsset, DeckLoadError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = ron::de::from_bytes::<DeckFile>(&bytes)?;
+        let mut problems = Vec::new();
+        let mut used_powers = Vec::new();
+        let mut shapes = Vec::with_capacity(file.shapes.len());
+        let mut weights = Vec::with_capacity(file.shapes.len());
+        for (index, shape) in file.shapes.into_iter().enumerate() {
+            weights.push(shape.weight);
+            let shape = shape.into_shape(index, &mut problems);
+            for (_, power) in shape.powers.iter() {
+                if !used_powers.contains(power) {
+                    used_powers.push(*power);
+                }
+            }
+            shapes.push(shape);
+        }
+        for power in used_powers {
+            if load_context.read_asset_bytes(power.icon()).await.is_err() {
+                problems.push(DeckProblem::MissingIcon {
+                    power,
+                    path: power.icon().into(),
+                });
+            }
+        }
+        Ok(DeckAsset {
+            name: file.name,
+            deck: Deck::from_weighted(shapes, weights),
+            problems,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["deck.ron"]
+    }
+}
+
+/// every deck in the `decks` folder, they are watched and the selected one is copied into `Deck` whenever it changes
+#[derive(Resource)]
+pub struct DeckLibrary(pub Handle<LoadedFolder>);
+
+impl FromWorld for DeckLibrary {
+    fn from_world(world: &mut World) -> Self {
+        DeckLibrary(world.resource::<AssetServer>().load_folder("decks"))
+    }
+}
+
+fn apply_deck_asset(
+    mut events: EventReader<AssetEvent<DeckAsset>>,
+    decks: Res<Assets<DeckAsset>>,
+    selected: Res<SelectedDeck>,
+    mut deck: ResMut<Deck>,
+    mut report: ResMut<DeckReport>,
+    run: Option<Res<crate::run::Run>>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(asset) = decks.get(*id) else {
+            continue;
+        };
+        let mut problems = asset.problems.clone();
+        problems.extend(asset.deck.validate(&Board::default()));
+        if !problems.is_empty() {
+            for problem in problems.iter() {
+                error!("Deck {}: {problem}", asset.name);
+            }
+            *report = DeckReport {
+                deck: asset.name.clone(),
+                problems,
+            };
+            continue;
+        }
+        info!("Loaded deck {}", asset.name);
+        // a run owns its deck until it is over
+        if asset.name != selected.0 || run.is_some() {
+            continue;
+        }
+        *deck = asset.deck.clone();
+    }
+}
+
+fn report_failed_deck(
+    mut events: EventReader<AssetLoadFailedEvent<DeckAsset>>,
+    mut report: ResMut<DeckReport>,
+) {
+    for event in events.read() {
+        error!("Failed to load deck {}: {}", event.path, event.error);
+        *report = DeckReport {
+            deck: event.path.to_string(),
+            problems: vec![DeckProblem::Load(event.error.to_string())],
+        };
+    }
+}
@@ -0,0 +1,213 @@
+use bevy::{platform_support::collections::HashSet, prelude::*};
+
+use super::Deck;
+use crate::{blocks::Power, board::Board};
+
+/// the most copies of one shape a bag can hold
+pub const MAX_WEIGHT: f32 = 64.;
+
+/// the smallest weight a shape can have other than 0, so it turns up at least once every 64 bags
+pub const MIN_WEIGHT: f32 = 1. / 64.;
+
+/// the problems found in the last deck that was loaded, shown in game until dismissed
+#[derive(Resource, Default)]
+pub struct DeckReport {
+    pub deck: String,
+    pub problems: Vec<DeckProblem>,
+}
+
+#[derive(Clone, Debug)]
+pub enum DeckProblem {
+    /// the file could not be read at all
+    Load(String),
+    NotFound(String),
+    NoShapes,
+    Empty {
+        shape: usize,
+    },
+    /// `split_shape` would cut the shape apart as soon as it spawned
+    Disconnected {
+        shape: usize,
+    },
+    DuplicateBlock {
+        shape: usize,
+        block: IVec2,
+    },
+    DuplicateShape {
+        shape: usize,
+        of: usize,
+    },
+    TooWide {
+        shape: usize,
+        width: i32,
+        board: i32,
+    },
+    InvalidColor {
+        shape: usize,
+        color: String,
+    },
+    UnknownPower {
+        shape: usize,
+        power: String,
+    },
+    PowerOutOfRange {
+        shape: usize,
+        block: usize,
+    },
+    InvalidWeight {
+        shape: usize,
+        weight: f32,
+    },
+    /// rotating around the pivot would put blocks between cells
+    InvalidPivot {
+        shape: usize,
+        pivot: Vec2,
+    },
+    MissingIcon {
+        power: Power,
+        path: String,
+    },
+}
+
+impl std::fmt::Display for DeckProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeckProblem::Load(e) => write!(f, "Could not load deck, {e}"),
+            DeckProblem::NotFound(name) => write!(f, "No deck called {name}"),
+            DeckProblem::NoShapes => write!(f, "Deck has no shapes to draw"),
+            DeckProblem::Empty { shape } => write!(f, "Shape {shape} has no blocks"),
+            DeckProblem::Disconnected { shape } => {
+                write!(f, "Shape {shape} is not connected")
+            }
+            DeckProblem::DuplicateBlock { shape, block } => {
+                write!(f, "Shape {shape} has block {},{} twice", block.x, block.y)
+            }
+            DeckProblem::DuplicateShape { shape, of } => {
+                write!(f, "Shape {shape} is the same as shape {of}")
+            }
+            DeckProblem::TooWide {
+                shape,
+                width,
+                board,
+            } => write!(
+                f,
+                "Shape {shape} is {width} wide but the board is {board} wide"
+            ),
+            DeckProblem::InvalidColor { shape, color } => {
+                write!(f, "Shape {shape} has invalid colour {color}")
+            }
+            DeckProblem::UnknownPower { shape, power } => {
+                write!(f, "Shape {shape} has unknown power {power}")
+            }
+            DeckProblem::PowerOutOfRange { shape, block } => {
+                write!(f, "Shape {shape} has a power on missing block {block}")
+            }
+            DeckProblem::InvalidWeight { shape, weight } => {
+                write!(
+                    f,
+                    "Shape {shape} has weight {weight}, weights are 0 or go from {MIN_WEIGHT} to {MAX_WEIGHT}"
+                )
+            }
+            DeckProblem::InvalidPivot { shape, pivot } => write!(
+                f,
+                "Shape {shape} pivot {},{} is not on a cell centre or corner",
+                pivot.x, pivot.y
+            ),
+            DeckProblem::MissingIcon { power, path } => {
+                write!(f, "Power {} is missing its icon {path}", power.name())
+            }
+        }
+    }
+}
+
+impl Deck {
+    /// check every shape can be played on `board`
+    pub fn validate(&self, board: &Board) -> Vec<DeckProblem> {
+        let mut problems = Vec::new();
+        if self.weights.iter().sum::<f32>() <= 0. {
+            problems.push(DeckProblem::NoShapes);
+        }
+        let mut seen: Vec<Vec<IVec2>> = Vec::with_capacity(self.shapes.len());
+        for (index, shape) in self.shapes.iter().enumerate() {
+            let weight = self.weight(index);
+            if !weight.is_finite() || (weight != 0. && !(MIN_WEIGHT..=MAX_WEIGHT).contains(&weight))
+            {
+                problems.push(DeckProblem::InvalidWeight {
+                    shape: index,
+                    weight,
+                });
+            }
+            let Some(first) = shape.blocks.first().copied() else {
+                problems.push(DeckProblem::Empty { shape: index });
+                seen.push(Vec::new());
+                continue;
+            };
+            let mut unique = HashSet::new();
+            for block in shape.blocks.iter() {
+                if !unique.insert(*block) {
+                    problems.push(DeckProblem::DuplicateBlock {
+                        shape: index,
+                        block: *block,
+                    });
+                }
+            }
+            if shape.connected(first).len() != unique.len() {
+                problems.push(DeckProblem::Disconnected { shape: index });
+            }
+            for (block, _) in shape.powers.iter() {
+                if *block >= shape.blocks.len() {
+                    problems.push(DeckProblem::PowerOutOfRange {
+                        shape: index,
+                        block: *block,
+                    });
+                }
+            }
+            let pivot = shape.pivot * 2.;
+            if pivot != pivot.round() || (pivot.x + pivot.y) as i32 % 2 != 0 {
+                problems.push(DeckProblem::InvalidPivot {
+                    shape: index,
+                    pivot: shape.pivot,
+                });
+            }
+            let min = shape.blocks.iter().copied().fold(first, IVec2::min);
+            let max = shape.blocks.iter().copied().fold(first, IVec2::max);
+            let width = max.x - min.x + 1;
+            if width > board.width() {
+                problems.push(DeckProblem::TooWide {
+                    shape: index,
+                    width,
+                    board: board.width(),
+                });
+            }
+            let normalized = canonical(unique.into_iter().collect());
+            if let Some(of) = seen.iter().position(|other| *other == normalized) {
+                problems.push(DeckProblem::DuplicateShape { shape: index, of });
+            }
+            seen.push(normalized);
+        }
+        problems
+    }
+}
+
+/// the same for every turn and position of the blocks, so shapes that play the same compare equal
+fn canonical(mut blocks: Vec<IVec2>) -> Vec<IVec2> {
+    let mut turns = Vec::with_capacity(4);
+    for _ in 0..4 {
+        let min = blocks.iter().copied().fold(IVec2::MAX, IVec2::min);
+        let mut turn = blocks.iter().map(|block| block - min).collect::<Vec<_>>();
+        turn.sort_by_key(|block| (block.y, block.x));
+        turns.push(turn);
+        blocks = blocks
+            .iter()
+            .map(|block| IVec2::new(-block.y, block.x))
+            .collect();
+    }
+    turns
+        .into_iter()
+        .min_by_key(|turn| {
+            turn.iter()
+                .map(|block| (block.y, block.x))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+}
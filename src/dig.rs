@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::{
+    garbage::{AddGarbage, Garbage},
+    player::{Player, PlayerSetup},
+    prelude::*,
+    ui::menus::Menu,
+};
+
+/// rows of garbage a dig starts with
+const DIG_ROWS: i32 = 10;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::Playing),
+        add_dig_garbage
+            .after(crate::player::spawn_players)
+            .run_if(resource_exists::<Dig>),
+    )
+    .add_systems(
+        FixedLast,
+        check_dig.run_if(resource_exists::<Dig>.and(in_state(GameState::Playing))),
+    )
+    .add_systems(OnExit(GameState::Playing), end_dig);
+}
+
+/// dig mode, the board starts full of garbage and the game is won once it is all cleared
+#[derive(Resource, Default)]
+pub struct Dig {
+    started: bool,
+}
+
+pub fn start_dig(mut commands: Commands, mut state: ResMut<NextState<GameState>>) {
+    commands.remove_resource::<crate::run::Run>();
+    commands.insert_resource(Dig::default());
+    commands.insert_resource(crate::mode::GameMode::Endless);
+    commands.insert_resource(PlayerSetup::default());
+    state.set(GameState::Playing);
+}
+
+fn add_dig_garbage(players: Query<Entity, With<Player>>, mut garbage: EventWriter<AddGarbage>) {
+    for board in &players {
+        garbage.write(AddGarbage {
+            board,
+            rows: DIG_ROWS,
+        });
+    }
+}
+
+fn check_dig(
+    mut dig: ResMut<Dig>,
+    garbage: Query<(), With<Garbage>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<Menu>>,
+) {
+    let left = garbage.iter().count();
+    if !dig.started {
+        dig.started = left > 0;
+        return;
+    }
+    if left > 0 {
+        return;
+    }
+    info!("Dig complete");
+    state.set(GameState::InMenu);
+    menu.set(Menu::Main);
+}
+
+fn end_dig(mut commands: Commands) {
+    commands.remove_resource::<Dig>();
+}
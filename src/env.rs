@@ -0,0 +1,354 @@
+//! a headless game for training agents, each `step` places one shape and returns what happened
+//! nothing is drawn and time only moves when the environment is stepped so it runs as fast as the rules allow
+
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    asset::AssetPlugin, image::TextureAtlasLayout, prelude::*, state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+
+pub use crate::board::{BoardText, BoardTextError, TextCell};
+use crate::{
+    ai::{find_placement, step_towards, without_shape, Placement},
+    blocks::{BlockImage, BlockSkin},
+    board::{BlockState, Board, OnBoard, Shape},
+    deck::{CurrentDeck, DeckLibrary, PlayerTarget, ReadInputs, TickInputs},
+    mode::{GameMode, TICK_HZ},
+    player::{Player, PlayerSetup},
+    prelude::*,
+    save::BoardContents,
+};
+
+/// ticks a single step may take before giving up on the shape ever locking
+const MAX_STEP_TICKS: u32 = 10_000;
+
+/// how long to wait for the deck files before playing with the built in deck
+const DECK_LOAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// where to put the current shape, columns and quarter turns as the bot protocol uses them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Action {
+    pub x: i32,
+    pub rotation: u32,
+}
+
+/// environments made by this process so far, each gets its own store
+static ENVIRONMENTS: AtomicUsize = AtomicUsize::new(0);
+
+/// everything an agent gets to see after each step
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Observation {
+    pub width: i32,
+    pub hight: i32,
+    /// row by row from the bottom, 0 is empty, 1 is a block and 2 is part of the shape being placed
+    pub cells: Vec<u8>,
+    /// blocks of the shape being placed relative to its center, `None` once the game is over
+    pub shape: Option<Vec<IVec2>>,
+    /// blocks of the upcoming shapes, next first
+    pub queue: Vec<Vec<IVec2>>,
+    pub score: i32,
+}
+
+/// the placement player one is being moved towards
+#[derive(Resource, Default)]
+struct EnvTarget(Option<Placement>);
+
+pub struct Environment {
+    app: App,
+    /// where this environment keeps records and settings, removed when it is dropped
+    store: PathBuf,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::new()
+    }
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        // keep training runs away from the players records and settings,
+        // and from each other so environments can run side by side
+        let store = std::env::temp_dir().join(format!(
+            "tetris-env-{}-{}",
+            std::process::id(),
+            ENVIRONMENTS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .insert_resource(bevy_pkv::PkvStore::new_in_dir(&store))
+            .add_plugins(crate::core_plugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1. / TICK_HZ,
+            )))
+            .init_resource::<EnvTarget>()
+            .add_systems(
+                FixedFirst,
+                env_inputs
+                    .in_set(ReadInputs)
+                    .after(crate::deck::take_inputs)
+                    .run_if(in_state(GameState::Playing)),
+            );
+        app.finish();
+        app.cleanup();
+        let mut env = Environment { app, store };
+        env.load_decks();
+        env
+    }
+
+    /// start a new game, the same seed always deals the same shapes
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        if self.playing() {
+            self.set_state(GameState::InMenu);
+            self.app.update();
+        }
+        let world = self.app.world_mut();
+        world.insert_resource(GameRng::seeded(seed));
+        world.insert_resource(PlayerSetup::default());
+        world.insert_resource(GameMode::Endless);
+        world.resource_mut::<EnvTarget>().0 = None;
+        self.set_state(GameState::Playing);
+        for _ in 0..MAX_STEP_TICKS {
+            self.app.update();
+            if self.active_shape().is_some() {
+                break;
+            }
+        }
+        self.observe()
+    }
+
+    /// place the current shape, the reward is the score it earned
+    /// a shape that can't reach `action` falls wherever gravity takes it
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        let before = self.score();
+        let Some(shape) = self.active_shape() else {
+            return (self.observe(), 0., true);
+        };
+        self.app.world_mut().resource_mut::<EnvTarget>().0 = self.placement(action);
+        for _ in 0..MAX_STEP_TICKS {
+            self.app.update();
+            if self.game_over() || self.active_shape().is_some_and(|active| active != shape) {
+                break;
+            }
+        }
+        self.app.world_mut().resource_mut::<EnvTarget>().0 = None;
+        let observation = self.observe();
+        let reward = (observation.score - before) as f32;
+        (observation, reward, self.game_over())
+    }
+
+    /// replace everything on the board with `text`, written as `BoardText` reads it
+    /// the next shape is dealt as usual if the text has no active blocks
+    pub fn set_board(&mut self, text: &str) -> Result<Observation, BoardTextError> {
+        let text = BoardText::parse(text)?;
+        self.app
+            .world_mut()
+            .run_system_cached_with(load_board_text, text)
+            .expect("load_board_text only takes world params")?;
+        Ok(self.observe())
+    }
+
+    /// every action that puts the current shape somewhere different
+    pub fn actions(&mut self) -> Vec<Action> {
+        let Some((shape, board)) = self.shape_and_board() else {
+            return Vec::new();
+        };
+        let mut found = Vec::new();
+        let mut actions = Vec::new();
+        for rotation in 0..4 {
+            for x in 0..board.width() {
+                let Some(placement) = find_placement(&shape, &board, x, None, rotation) else {
+                    continue;
+                };
+                if !found.contains(&placement) {
+                    found.push(placement);
+                    actions.push(Action { x, rotation });
+                }
+            }
+        }
+        actions
+    }
+
+    pub fn observe(&mut self) -> Observation {
+        let world = self.app.world_mut();
+        let mut shapes = world.query_filtered::<(&Shape, &OnBoard), With<PlayerTarget>>();
+        let shape = shapes.iter(world).next().map(|(shape, _)| shape.clone());
+        let mut players = world.query::<(&Player, &Board, &CurrentDeck, &Score)>();
+        let Some((_, board, deck, score)) =
+            players.iter(world).find(|(player, ..)| player.index == 0)
+        else {
+            return Observation::default();
+        };
+        let active = shape
+            .iter()
+            .flat_map(|shape| shape.blocks.iter().map(|block| shape.center + block))
+            .collect::<Vec<_>>();
+        let cells = (0..board.hight())
+            .flat_map(|y| (0..board.width()).map(move |x| IVec2::new(x, y)))
+            .map(|cell| match board.get(cell) {
+                BlockState::Contains(_) if active.contains(&cell) => 2,
+                BlockState::Contains(_) => 1,
+                _ => 0,
+            })
+            .collect();
+        Observation {
+            width: board.width(),
+            hight: board.hight(),
+            cells,
+            shape: shape.map(|shape| shape.blocks),
+            queue: deck.upcoming().map(|shape| shape.blocks.clone()).collect(),
+            score: score.0,
+        }
+    }
+
+    /// run until the deck files have loaded so every game is dealt from the selected deck
+    fn load_decks(&mut self) {
+        let started = Instant::now();
+        while started.elapsed() < DECK_LOAD_TIMEOUT {
+            self.app.update();
+            let world = self.app.world();
+            let library = &world.resource::<DeckLibrary>().0;
+            let server = world.resource::<AssetServer>();
+            if server.is_loaded_with_dependencies(library) || server.load_state(library).is_failed()
+            {
+                break;
+            }
+            std::thread::yield_now();
+        }
+        // let the loaded deck be applied
+        self.app.update();
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        self.app
+            .world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+    }
+
+    fn playing(&self) -> bool {
+        *self.app.world().resource::<State<GameState>>() == GameState::Playing
+    }
+
+    /// the game has ended, or is about to at the start of the next update
+    fn game_over(&self) -> bool {
+        let world = self.app.world();
+        !self.playing()
+            || matches!(
+                world.resource::<NextState<GameState>>(),
+                NextState::Pending(GameState::InMenu)
+            )
+    }
+
+    fn score(&mut self) -> i32 {
+        self.observe().score
+    }
+
+    fn active_shape(&mut self) -> Option<Entity> {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<Entity, (With<Shape>, With<PlayerTarget>)>()
+            .iter(world)
+            .next()
+    }
+
+    /// the shape being placed and the board without it
+    fn shape_and_board(&mut self) -> Option<(Shape, Board)> {
+        let world = self.app.world_mut();
+        let (shape, on_board) = world
+            .query_filtered::<(&Shape, &OnBoard), With<PlayerTarget>>()
+            .iter(world)
+            .next()?;
+        let board = world.get::<Board>(on_board.0)?;
+        Some((shape.clone(), without_shape(board, shape)))
+    }
+
+    fn placement(&mut self, action: Action) -> Option<Placement> {
+        let (shape, board) = self.shape_and_board()?;
+        find_placement(&shape, &board, action.x, None, action.rotation)
+    }
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.store);
+    }
+}
+
+fn env_inputs(
+    target: Res<EnvTarget>,
+    mut players: Query<(Entity, &Player, &Board, &mut TickInputs)>,
+    shapes: Query<(&Shape, &OnBoard), With<PlayerTarget>>,
+) {
+    let Some(placement) = &target.0 else {
+        return;
+    };
+    let Some((entity, _, board, mut inputs)) = players
+        .iter_mut()
+        .find(|(_, player, _, _)| player.index == 0)
+    else {
+        return;
+    };
+    let Some((shape, _)) = shapes.iter().find(|(_, on_board)| on_board.0 == entity) else {
+        return;
+    };
+    *inputs = step_towards(shape, &without_shape(board, shape), placement).unwrap_or_default();
+}
+
+fn load_board_text(
+    In(text): In<BoardText>,
+    mut commands: Commands,
+    mut players: Query<(Entity, &Player, &mut Board)>,
+    contents: BoardContents,
+    block_image: Res<BlockImage>,
+    skin: Res<BlockSkin>,
+) -> Result<(), BoardTextError> {
+    let Some((entity, _, mut board)) = players.iter_mut().find(|(_, player, _)| player.index == 0)
+    else {
+        return Ok(());
+    };
+    if text.width > board.width() || text.hight > board.hight() {
+        return Err(BoardTextError::DoesNotFit);
+    }
+    contents.clear(&mut commands, entity, &mut board);
+    text.spawn(&mut commands, entity, &mut board, &block_image, *skin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// play `steps` shapes from `seed`, cycling through the actions on offer
+    fn play(env: &mut Environment, seed: u64, steps: usize) -> Vec<Observation> {
+        let mut observations = vec![env.reset(seed)];
+        for step in 0..steps {
+            let actions = env.actions();
+            let Some(action) = actions.get(step % actions.len().max(1)).copied() else {
+                break;
+            };
+            let (observation, _, done) = env.step(action);
+            observations.push(observation);
+            if done {
+                break;
+            }
+        }
+        observations
+    }
+
+    #[test]
+    fn same_seed_plays_the_same() {
+        let mut env = Environment::new();
+        let first = play(&mut env, 7, 30);
+        let again = play(&mut env, 7, 30);
+        assert_eq!(first, again);
+        let mut other = Environment::new();
+        assert_eq!(first, play(&mut other, 7, 30));
+    }
+}
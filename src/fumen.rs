@@ -0,0 +1,1053 @@
+//! boards as fumen strings, the format players share setups and puzzles in
+//! only the first page is read or written, the queue goes in the quiz comment like `#Q=[](T)SZO`
+
+use bevy::{
+    platform_support::{collections::HashSet, hash::FixedHasher},
+    prelude::*,
+};
+
+use crate::{
+    blocks::{Block, BlockSprites},
+    board::{spawn_board_block, spawn_static_block, BlockState, Board, OnBoard, Shape},
+    deck::{CurrentDeck, PlayerTarget},
+    garbage::GARBAGE_COLOR,
+    palette::{shape_key, BlockPalette},
+    player::Player,
+    practice::Practice,
+    prelude::*,
+    puzzle::ActivePuzzle,
+    save::BoardContents,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, read_fumen_arg)
+        .add_systems(
+            OnEnter(GameState::Playing),
+            load_pending_fumen
+                .after(crate::player::spawn_players)
+                .after(crate::practice::snapshot_start)
+                .run_if(resource_exists::<PendingFumen>),
+        )
+        .add_systems(
+            Update,
+            (copy_fumen, paste_fumen).run_if(
+                in_state(GameState::Playing)
+                    .and(resource_exists::<Practice>.or(resource_exists::<ActivePuzzle>)),
+            ),
+        );
+}
+
+const FIELD_WIDTH: i32 = 10;
+/// rows fumen has above the floor, it also has one row of garbage below it that is not used here
+const FIELD_TOP: i32 = 23;
+const FIELD_BLOCKS: u32 = (FIELD_WIDTH * (FIELD_TOP + 1)) as u32;
+const TABLE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const QUIZ: &str = "#Q=";
+
+#[derive(Debug)]
+pub enum FumenError {
+    /// not a fumen string, or one from a version other than 115
+    Version,
+    /// the string ends early or has characters fumen never writes
+    Corrupt(String),
+    /// fumen fields are ten wide and twenty three high
+    DoesNotFit,
+    /// fumen only knows the seven tetrominoes
+    UnknownShape,
+}
+
+impl std::fmt::Display for FumenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FumenError::Version => write!(f, "not a v115 fumen"),
+            FumenError::Corrupt(e) => write!(f, "corrupt fumen: {e}"),
+            FumenError::DoesNotFit => write!(f, "board does not fit a fumen field"),
+            FumenError::UnknownShape => write!(f, "shape is not a tetromino"),
+        }
+    }
+}
+
+impl std::error::Error for FumenError {}
+
+/// what each cell of a fumen field holds, in fumens numbering from 1
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Piece {
+    I,
+    L,
+    O,
+    Z,
+    T,
+    J,
+    S,
+    Gray,
+}
+
+impl Piece {
+    const TETROMINOES: [Piece; 7] = [
+        Piece::I,
+        Piece::L,
+        Piece::O,
+        Piece::Z,
+        Piece::T,
+        Piece::J,
+        Piece::S,
+    ];
+
+    fn code(self) -> u32 {
+        self as u32 + 1
+    }
+
+    fn from_code(code: u32) -> Option<Piece> {
+        match code {
+            1..=7 => Some(Piece::TETROMINOES[code as usize - 1]),
+            8 => Some(Piece::Gray),
+            _ => None,
+        }
+    }
+
+    fn letter(self) -> char {
+        match self {
+            Piece::I => 'I',
+            Piece::L => 'L',
+            Piece::O => 'O',
+            Piece::Z => 'Z',
+            Piece::T => 'T',
+            Piece::J => 'J',
+            Piece::S => 'S',
+            Piece::Gray => 'X',
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Piece> {
+        Piece::TETROMINOES
+            .into_iter()
+            .find(|piece| piece.letter() == letter)
+    }
+
+    /// blocks facing the way the piece spawns, around the cell it turns on
+    fn blocks(self) -> [IVec2; 4] {
+        let blocks = match self {
+            Piece::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+            Piece::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+            Piece::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            Piece::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+            Piece::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+            Piece::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+            Piece::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+            Piece::Gray => [(0, 0); 4],
+        };
+        blocks.map(|(x, y)| IVec2::new(x, y))
+    }
+
+    /// the colour the classic deck gives this piece
+    fn color(self) -> Color {
+        use bevy::color::palettes::css;
+        match self {
+            Piece::I => css::LIGHT_BLUE.into(),
+            Piece::L => css::ORANGE.into(),
+            Piece::O => css::YELLOW.into(),
+            Piece::Z => css::RED.into(),
+            Piece::T => css::PURPLE.into(),
+            Piece::J => css::DARK_BLUE.into(),
+            Piece::S => css::LIGHT_GREEN.into(),
+            Piece::Gray => GARBAGE_COLOR,
+        }
+    }
+
+    /// `I` and `O` turn around the corner between cells rather than a cell
+    fn pivot(self) -> Vec2 {
+        match self {
+            Piece::I => Vec2::new(0.5, -0.5),
+            Piece::O => Vec2::new(0.5, 0.5),
+            _ => Vec2::ZERO,
+        }
+    }
+
+    /// the piece as a shape turned to `rotation`, centered on the cell it turns on
+    fn shape(self, rotation: Rotation) -> Shape {
+        let mut shape = Shape {
+            split: false,
+            center: IVec2::ZERO,
+            blocks: self.blocks().map(|block| rotation.turn(block)).to_vec(),
+            color: self.color(),
+            center_of_mass: Vec2::ZERO,
+            pivot: rotation.turn_pivot(self.pivot()),
+            powers: Vec::new(),
+        };
+        shape.calc_center();
+        shape
+    }
+
+    /// the tetromino `shape` is, whichever way it is turned
+    fn of_shape(shape: &Shape) -> Option<Piece> {
+        let key = shape_key(&shape.blocks);
+        Piece::TETROMINOES
+            .into_iter()
+            .find(|piece| shape_key(&piece.blocks()) == key)
+    }
+
+    /// the piece a block was drawn as, anything that is not a tetromino colour is garbage
+    fn of_color(color: Color, palette: &BlockPalette) -> Piece {
+        Piece::TETROMINOES
+            .into_iter()
+            .find(|piece| palette.color(&piece.shape(Rotation::Spawn)) == color)
+            .unwrap_or(Piece::Gray)
+    }
+
+    /// fumen keeps some pieces a cell away from the cell they turn on
+    fn offset(self, rotation: Rotation) -> IVec2 {
+        match (self, rotation) {
+            (Piece::O, Rotation::Left) => IVec2::new(1, -1),
+            (Piece::O, Rotation::Reverse) => IVec2::new(1, 0),
+            (Piece::O, Rotation::Spawn) => IVec2::new(0, -1),
+            (Piece::I, Rotation::Reverse) => IVec2::new(1, 0),
+            (Piece::I, Rotation::Left) => IVec2::new(0, -1),
+            (Piece::S, Rotation::Spawn) => IVec2::new(0, -1),
+            (Piece::S, Rotation::Right) => IVec2::new(-1, 0),
+            (Piece::Z, Rotation::Spawn) => IVec2::new(0, -1),
+            (Piece::Z, Rotation::Left) => IVec2::new(1, 0),
+            _ => IVec2::ZERO,
+        }
+    }
+}
+
+/// in the order fumen numbers them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Rotation {
+    Reverse,
+    Right,
+    Spawn,
+    Left,
+}
+
+impl Rotation {
+    const ALL: [Rotation; 4] = [
+        Rotation::Reverse,
+        Rotation::Right,
+        Rotation::Spawn,
+        Rotation::Left,
+    ];
+
+    fn turn(self, block: IVec2) -> IVec2 {
+        match self {
+            Rotation::Spawn => block,
+            Rotation::Right => IVec2::new(block.y, -block.x),
+            Rotation::Reverse => -block,
+            Rotation::Left => IVec2::new(-block.y, block.x),
+        }
+    }
+
+    fn turn_pivot(self, pivot: Vec2) -> Vec2 {
+        self.turn((pivot * 2.).as_ivec2()).as_vec2() / 2.
+    }
+}
+
+/// a board as the first page of a fumen
+#[derive(Clone, Default)]
+pub struct FumenBoard {
+    /// every filled cell apart from the active shape, from the floor up
+    pub cells: Vec<(IVec2, Piece)>,
+    /// the shape being placed, where it is on the board
+    pub active: Option<Shape>,
+    /// the shapes after it, next first
+    pub queue: Vec<Shape>,
+}
+
+/// which piece `shape` is, which way it faces and the cell it turns on
+fn locate(shape: &Shape) -> Option<(Piece, Rotation, IVec2)> {
+    let piece = Piece::of_shape(shape)?;
+    let cells = shape
+        .blocks
+        .iter()
+        .map(|block| shape.center + block)
+        .collect::<Vec<_>>();
+    for rotation in Rotation::ALL {
+        let blocks = piece.blocks().map(|block| rotation.turn(block));
+        for block in blocks {
+            let center = cells[0] - block;
+            if blocks.iter().all(|block| cells.contains(&(center + block))) {
+                return Some((piece, rotation, center));
+            }
+        }
+    }
+    None
+}
+
+fn field_index(cell: IVec2) -> Result<usize, FumenError> {
+    if cell.x < 0 || cell.x >= FIELD_WIDTH || cell.y < 0 || cell.y >= FIELD_TOP {
+        return Err(FumenError::DoesNotFit);
+    }
+    Ok(((FIELD_TOP - 1 - cell.y) * FIELD_WIDTH + cell.x) as usize)
+}
+
+fn field_cell(index: u32) -> IVec2 {
+    let index = index as i32;
+    IVec2::new(index % FIELD_WIDTH, FIELD_TOP - 1 - index / FIELD_WIDTH)
+}
+
+fn push(data: &mut Vec<u32>, mut value: u32, digits: usize) {
+    for _ in 0..digits {
+        data.push(value % 64);
+        value /= 64;
+    }
+}
+
+/// the letters of every shape in `shapes`
+fn letters<'a>(shapes: impl Iterator<Item = &'a Shape>) -> Result<String, FumenError> {
+    shapes
+        .map(|shape| Piece::of_shape(shape).map(Piece::letter))
+        .collect::<Option<String>>()
+        .ok_or(FumenError::UnknownShape)
+}
+
+/// percent escapes like javascripts `escape`, which fumen runs comments through
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) {
+                c.to_string()
+            } else {
+                format!("%{:02X}", c as u32)
+            }
+        })
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let rest = chars.as_str();
+        let (digits, len) = match rest.strip_prefix('u') {
+            Some(rest) => (rest.get(..4), 5),
+            None => (rest.get(..2), 2),
+        };
+        match digits
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .and_then(char::from_u32)
+        {
+            Some(decoded) => {
+                out.push(decoded);
+                chars = rest[len..].chars();
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn encode(board: &FumenBoard) -> Result<String, FumenError> {
+    let mut field = vec![0; FIELD_BLOCKS as usize];
+    for (cell, piece) in board.cells.iter() {
+        field[field_index(*cell)?] = piece.code();
+    }
+    let mut data = Vec::new();
+    // each run of cells that changed the same way since the last page, this is the first so that is from empty
+    let mut start = 0;
+    while start < field.len() {
+        let code = field[start];
+        let run = field[start..]
+            .iter()
+            .take_while(|cell| **cell == code)
+            .count();
+        push(&mut data, (code + 8) * FIELD_BLOCKS + run as u32 - 1, 2);
+        start += run;
+    }
+    if field.iter().all(|cell| *cell == 0) {
+        // how many pages after this one have the same field
+        push(&mut data, 0, 1);
+    }
+
+    let (piece, rotation, position) = match &board.active {
+        Some(shape) => {
+            let (piece, rotation, center) = locate(shape).ok_or(FumenError::UnknownShape)?;
+            let index = field_index(center - piece.offset(rotation))?;
+            (piece.code(), rotation as u32, index as u32)
+        }
+        None => (0, 0, 0),
+    };
+    let current = board
+        .active
+        .as_ref()
+        .or(board.queue.first())
+        .map(|shape| letters(std::iter::once(shape)))
+        .transpose()?;
+    let comment = match current {
+        Some(current) => {
+            let skip = if board.active.is_some() { 0 } else { 1 };
+            format!(
+                "{QUIZ}[]({current}){}",
+                letters(board.queue.iter().skip(skip))?
+            )
+        }
+        None => String::new(),
+    };
+    let lock = true;
+    let colorize = true;
+    let mut action = u32::from(!lock);
+    action = action * 2 + u32::from(!comment.is_empty());
+    action = action * 2 + u32::from(colorize);
+    // mirror and rise are never set
+    action *= 4;
+    action = action * FIELD_BLOCKS + position;
+    action = action * 4 + rotation;
+    action = action * 8 + piece;
+    push(&mut data, action, 3);
+
+    if !comment.is_empty() {
+        let escaped = escape(&comment).chars().collect::<Vec<_>>();
+        push(&mut data, escaped.len() as u32, 2);
+        for chunk in escaped.chunks(4) {
+            let mut value = 0;
+            for (index, c) in chunk.iter().enumerate() {
+                let code = COMMENT_TABLE.find(*c).unwrap_or(0) as u32;
+                value += code * 96u32.pow(index as u32);
+            }
+            push(&mut data, value, 5);
+        }
+    }
+
+    let text = data
+        .iter()
+        .map(|digit| TABLE.as_bytes()[*digit as usize] as char)
+        .collect::<String>();
+    // fumen breaks the data up with ? so long strings wrap, 42 characters then every 47
+    let mut out = String::from("v115@");
+    for (index, c) in text.chars().enumerate() {
+        if index >= 42 && (index - 42) % 47 == 0 {
+            out.push('?');
+        }
+        out.push(c);
+    }
+    Ok(out)
+}
+
+struct Reader {
+    data: Vec<u32>,
+    at: usize,
+}
+
+impl Reader {
+    fn take(&mut self, digits: usize) -> Result<u32, FumenError> {
+        let Some(values) = self.data.get(self.at..self.at + digits) else {
+            return Err(FumenError::Corrupt("ends early".into()));
+        };
+        self.at += digits;
+        Ok(values
+            .iter()
+            .rev()
+            .fold(0, |value, digit| value * 64 + digit))
+    }
+}
+
+/// read the first page of `text`, which can be a whole fumen url
+pub fn decode(text: &str) -> Result<FumenBoard, FumenError> {
+    let text = text.trim();
+    let Some(start) = text.find("115@") else {
+        return Err(FumenError::Version);
+    };
+    let data = text[start + 4..]
+        .chars()
+        .filter(|c| *c != '?')
+        .map(|c| {
+            TABLE
+                .find(c)
+                .map(|digit| digit as u32)
+                .ok_or_else(|| FumenError::Corrupt(format!("{c:?} is not a fumen character")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut reader = Reader { data, at: 0 };
+
+    let mut board = FumenBoard::default();
+    let mut index = 0;
+    while index < FIELD_BLOCKS {
+        let run = reader.take(2)?;
+        let (diff, count) = (run / FIELD_BLOCKS, run % FIELD_BLOCKS + 1);
+        if diff == 8 && count == FIELD_BLOCKS {
+            reader.take(1)?;
+        }
+        if index + count > FIELD_BLOCKS || diff < 8 {
+            return Err(FumenError::Corrupt("field does not add up".into()));
+        }
+        if let Some(piece) = Piece::from_code(diff - 8) {
+            for cell in index..index + count {
+                let cell = field_cell(cell);
+                // the garbage row is below the floor
+                if cell.y >= 0 {
+                    board.cells.push((cell, piece));
+                }
+            }
+        } else if diff != 8 {
+            return Err(FumenError::Corrupt(format!("unknown piece {}", diff - 8)));
+        }
+        index += count;
+    }
+    board.cells.reverse();
+
+    let mut action = reader.take(3)?;
+    let piece = action % 8;
+    action /= 8;
+    let rotation = Rotation::ALL[(action % 4) as usize];
+    action /= 4;
+    let position = action % FIELD_BLOCKS;
+    action /= FIELD_BLOCKS;
+    // rise, mirror and colorize only matter for later pages
+    action /= 8;
+    let has_comment = action % 2 == 1;
+    if piece != 0 {
+        let piece = Piece::from_code(piece)
+            .filter(|piece| *piece != Piece::Gray)
+            .ok_or(FumenError::UnknownShape)?;
+        let mut shape = piece.shape(rotation);
+        shape.center = field_cell(position) + piece.offset(rotation);
+        board.active = Some(shape);
+    }
+
+    if has_comment {
+        let length = reader.take(2)? as usize;
+        let mut escaped = String::new();
+        while escaped.len() < length {
+            let mut value = reader.take(5)?;
+            for _ in 0..4.min(length - escaped.len()) {
+                let c = COMMENT_TABLE.as_bytes()[(value % 96) as usize % COMMENT_TABLE.len()];
+                escaped.push(c as char);
+                value /= 96;
+            }
+        }
+        let comment = unescape(&escaped);
+        if let Some(quiz) = comment.strip_prefix(QUIZ) {
+            board.queue = read_quiz(quiz, board.active.is_some());
+        }
+    }
+    Ok(board)
+}
+
+/// the shapes in a quiz like `[H](C)NEXT`, the current piece is already placed when the page has one
+/// there is no hold so the held piece is left out
+fn read_quiz(quiz: &str, placed: bool) -> Vec<Shape> {
+    let mut chars = quiz.chars().peekable();
+    let mut queue = Vec::new();
+    let mut current = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                chars.find(|c| *c == ']');
+            }
+            '(' => {
+                current = chars.next_if(|c| *c != ')').and_then(Piece::from_letter);
+                chars.find(|c| *c == ')');
+            }
+            c => match Piece::from_letter(c) {
+                Some(piece) => queue.push(piece),
+                None => break,
+            },
+        }
+    }
+    current
+        .filter(|_| !placed)
+        .into_iter()
+        .chain(queue)
+        .map(|piece| piece.shape(Rotation::Spawn))
+        .collect()
+}
+
+/// the cells of each tetromino split into groups that touch, each group becomes one locked shape
+fn groups(cells: &[(IVec2, Piece)]) -> Vec<(Piece, Vec<IVec2>)> {
+    let mut left = cells
+        .iter()
+        .filter(|(_, piece)| *piece != Piece::Gray)
+        .copied()
+        .collect::<Vec<_>>();
+    let mut groups = Vec::new();
+    while let Some((start, piece)) = left.pop() {
+        let mut group = vec![start];
+        let mut at = 0;
+        while let Some(cell) = group.get(at).copied() {
+            at += 1;
+            for side in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                if let Some(index) = left.iter().position(|other| *other == (cell + side, piece)) {
+                    group.push(left.swap_remove(index).0);
+                }
+            }
+        }
+        groups.push((piece, group));
+    }
+    groups
+}
+
+/// which of `groups` would stay put under gravity, resting on the floor, a gray cell or another group that stays
+/// fumen lets cells float where lines were cleared under them, those groups have to be kept from falling
+fn supported(groups: &[(Piece, Vec<IVec2>)], gray: &[IVec2]) -> Vec<bool> {
+    let mut supported = vec![false; groups.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, (_, cells)) in groups.iter().enumerate() {
+            if supported[index] {
+                continue;
+            }
+            let rests = cells.iter().any(|cell| {
+                let below = cell - IVec2::Y;
+                below.y < 0
+                    || gray.contains(&below)
+                    || groups.iter().enumerate().any(|(other, (_, cells))| {
+                        other != index && supported[other] && cells.contains(&below)
+                    })
+            });
+            if rests {
+                supported[index] = true;
+                changed = true;
+            }
+        }
+    }
+    supported
+}
+
+/// a fumen from the command line to play once the game starts
+#[derive(Resource)]
+struct PendingFumen(FumenBoard);
+
+fn read_fumen_arg(mut commands: Commands) {
+    let mut args = std::env::args().skip_while(|arg| arg != "--fumen").skip(1);
+    let Some(text) = args.next() else {
+        return;
+    };
+    match decode(&text) {
+        Ok(board) => {
+            commands.insert_resource(PendingFumen(board));
+            commands.run_system_cached(crate::practice::start_practice);
+        }
+        Err(e) => error!("Failed to read fumen: {e}"),
+    }
+}
+
+/// everything needed to put a fumen on the board of a player
+#[derive(bevy::ecs::system::SystemParam)]
+struct FumenTarget<'w, 's> {
+    commands: Commands<'w, 's>,
+    players: Query<'w, 's, (Entity, &'static mut Board, &'static mut CurrentDeck), With<Player>>,
+    contents: BoardContents<'w, 's>,
+    palette: Res<'w, BlockPalette>,
+    sprites: BlockSprites<'w>,
+    practice: Option<Res<'w, Practice>>,
+    puzzle: Option<ResMut<'w, ActivePuzzle>>,
+}
+
+impl FumenTarget<'_, '_> {
+    fn load(&mut self, fumen: FumenBoard) -> Result<(), FumenError> {
+        let Ok((player, mut board, mut current)) = self.players.single_mut() else {
+            return Ok(());
+        };
+        let fits = |cell: IVec2| {
+            cell.cmpge(IVec2::ZERO).all() && cell.x < board.width() && cell.y < board.hight()
+        };
+        let active_cells = fumen
+            .active
+            .iter()
+            .flat_map(|shape| shape.blocks.iter().map(|block| shape.center + block))
+            .collect::<Vec<_>>();
+        if !fumen.cells.iter().all(|(cell, _)| fits(*cell))
+            || !active_cells
+                .iter()
+                .all(|cell| fits(*cell) && fumen.cells.iter().all(|(filled, _)| filled != cell))
+        {
+            return Err(FumenError::DoesNotFit);
+        }
+        self.contents.clear(&mut self.commands, player, &mut board);
+        // gray cells are garbage, which never falls
+        let gray = fumen
+            .cells
+            .iter()
+            .filter(|(_, piece)| *piece == Piece::Gray)
+            .map(|(cell, _)| *cell)
+            .collect::<Vec<_>>();
+        for cell in gray.iter() {
+            spawn_static_block(
+                &mut self.commands,
+                player,
+                &mut board,
+                *cell,
+                self.sprites.sprite(GARBAGE_COLOR),
+                None,
+            );
+        }
+        let groups = groups(&fumen.cells);
+        let supported = supported(&groups, &gray);
+        let (groups, floating): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .zip(supported)
+            .partition(|(_, supported)| *supported);
+        // floating groups would fall on the first step, so they stay where the fumen drew them
+        for ((piece, cells), _) in floating {
+            let color = self.palette.color(&piece.shape(Rotation::Spawn));
+            for cell in cells {
+                spawn_static_block(
+                    &mut self.commands,
+                    player,
+                    &mut board,
+                    cell,
+                    self.sprites.sprite(color),
+                    None,
+                );
+            }
+        }
+        let mut shapes = groups
+            .into_iter()
+            .map(|((piece, cells), _)| {
+                let mut shape = piece.shape(Rotation::Spawn);
+                shape.color = self.palette.color(&shape);
+                shape.center = cells[0];
+                shape.blocks = cells.iter().map(|cell| cell - cells[0]).collect();
+                // a group is not a piece any more so it has nothing sensible to turn around
+                shape.pivot = Vec2::ZERO;
+                (shape, false)
+            })
+            .collect::<Vec<_>>();
+        if let Some(mut shape) = fumen.active {
+            shape.color = self.palette.color(&shape);
+            shapes.push((shape, true));
+        }
+        for (mut shape, active) in shapes {
+            shape.calc_center();
+            // the blocks are spawned here so spawn_shape has to skip it
+            shape.split = true;
+            let cells = shape
+                .blocks
+                .iter()
+                .map(|block| shape.center + block)
+                .collect::<Vec<_>>();
+            let color = shape.color;
+            let mut entity = self.commands.spawn((shape, OnBoard(player)));
+            if active {
+                entity.insert(PlayerTarget::default());
+            }
+            let entity = entity.id();
+            for cell in cells {
+                spawn_board_block(
+                    &mut self.commands,
+                    player,
+                    &mut board,
+                    cell,
+                    self.sprites.sprite(color),
+                    None,
+                    Block {
+                        shape: Some(entity),
+                        moved: false,
+                        effects: HashSet::with_hasher(FixedHasher),
+                    },
+                );
+            }
+        }
+        // puzzles end once their pieces run out, practice carries on with the deck
+        if let Some(puzzle) = self.puzzle.as_mut() {
+            puzzle.paste();
+            *current = CurrentDeck::fixed(fumen.queue);
+        } else {
+            current.deal_first(fumen.queue);
+        }
+        // undoing back past the fumen would bring back a board that was never played
+        if self.practice.is_some() {
+            self.commands
+                .run_system_cached(crate::practice::snapshot_start);
+        }
+        Ok(())
+    }
+}
+
+fn load_pending_fumen(mut commands: Commands, pending: Res<PendingFumen>, mut target: FumenTarget) {
+    commands.remove_resource::<PendingFumen>();
+    if let Err(e) = target.load(pending.0.clone()) {
+        error!("Failed to load fumen: {e}");
+    }
+}
+
+fn paste_fumen(input: Res<ButtonInput<KeyCode>>, mut target: FumenTarget) {
+    if !(input.pressed(KeyCode::ControlLeft) || input.pressed(KeyCode::ControlRight))
+        || !input.just_pressed(KeyCode::KeyV)
+    {
+        return;
+    }
+    let text = match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("Failed to read clipboard: {e}");
+            return;
+        }
+    };
+    match decode(&text).and_then(|fumen| target.load(fumen)) {
+        Ok(()) => info!("Loaded fumen"),
+        Err(e) => error!("Failed to load fumen: {e}"),
+    }
+}
+
+fn copy_fumen(
+    input: Res<ButtonInput<KeyCode>>,
+    players: Query<(Entity, &Board, &CurrentDeck), With<Player>>,
+    active: Query<(&Shape, &OnBoard), With<PlayerTarget>>,
+    blocks: Query<&Sprite, With<Block>>,
+    palette: Res<BlockPalette>,
+) {
+    if !(input.pressed(KeyCode::ControlLeft) || input.pressed(KeyCode::ControlRight))
+        || !input.just_pressed(KeyCode::KeyC)
+    {
+        return;
+    }
+    let Ok((player, board, current)) = players.single() else {
+        return;
+    };
+    let active = active
+        .iter()
+        .find(|(_, on_board)| on_board.0 == player)
+        .map(|(shape, _)| shape.clone());
+    let mut fumen = FumenBoard {
+        active,
+        queue: current.upcoming().cloned().collect(),
+        ..Default::default()
+    };
+    for y in 0..board.hight() {
+        for x in 0..board.width() {
+            let cell = IVec2::new(x, y);
+            let BlockState::Contains(entity) = board.get(cell) else {
+                continue;
+            };
+            let in_active = fumen.active.as_ref().is_some_and(|shape| {
+                shape
+                    .blocks
+                    .iter()
+                    .any(|block| shape.center + block == cell)
+            });
+            if in_active {
+                continue;
+            }
+            let piece = blocks.get(entity).map_or(Piece::Gray, |sprite| {
+                Piece::of_color(sprite.color, &palette)
+            });
+            fumen.cells.push((cell, piece));
+        }
+    }
+    let text = match encode(&fumen) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("Failed to write fumen: {e}");
+            return;
+        }
+    };
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text.clone())) {
+        Ok(()) => info!("Copied fumen {text}"),
+        Err(e) => error!("Failed to copy fumen: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(shape: &Shape) -> Vec<IVec2> {
+        let mut cells = shape
+            .blocks
+            .iter()
+            .map(|block| shape.center + block)
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    fn sorted(mut cells: Vec<(IVec2, Piece)>) -> Vec<(IVec2, Piece)> {
+        cells.sort_by_key(|(cell, _)| (cell.y, cell.x));
+        cells
+    }
+
+    fn at(cells: &[(i32, i32)]) -> Vec<IVec2> {
+        let mut cells = cells
+            .iter()
+            .map(|(x, y)| IVec2::new(*x, *y))
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    #[test]
+    fn decodes_empty_field() {
+        let board = decode("v115@vhAAgH").unwrap();
+        assert!(board.cells.is_empty());
+        assert!(board.active.is_none());
+        assert!(board.queue.is_empty());
+        assert_eq!(encode(&board).unwrap(), "v115@vhAAgH");
+    }
+
+    #[test]
+    fn decodes_t_in_every_rotation() {
+        let cases = [
+            ("v115@vhAVQJ", at(&[(3, 0), (4, 0), (5, 0), (4, 1)])),
+            ("v115@vhANLJ", at(&[(4, 0), (4, 1), (4, 2), (5, 1)])),
+            ("v115@vhAFLJ", at(&[(4, 0), (3, 1), (4, 1), (5, 1)])),
+            ("v115@vhAdLJ", at(&[(4, 0), (4, 1), (4, 2), (3, 1)])),
+        ];
+        for (text, expected) in cases {
+            let board = decode(text).unwrap();
+            let active = board.active.as_ref().unwrap();
+            assert_eq!(Piece::of_shape(active), Some(Piece::T));
+            assert_eq!(cells(active), expected, "{text}");
+            assert_eq!(encode(&board).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn decodes_quiz_queue() {
+        let text = "v115@vhAAgWXAFLDmClcJSAVDEHBEooRBUoAVBzHrBA";
+        let board = decode(text).unwrap();
+        assert!(board.active.is_none());
+        assert_eq!(letters(board.queue.iter()).unwrap(), "TSZO");
+        assert_eq!(encode(&board).unwrap(), text);
+    }
+
+    #[test]
+    fn decodes_field() {
+        let text = "v115@RhwwIeB8zhNeAgH";
+        let board = decode(text).unwrap();
+        let expected = vec![
+            (IVec2::new(0, 0), Piece::Gray),
+            (IVec2::new(1, 0), Piece::Gray),
+            (IVec2::new(2, 0), Piece::I),
+            (IVec2::new(3, 0), Piece::I),
+            (IVec2::new(4, 0), Piece::I),
+            (IVec2::new(5, 0), Piece::I),
+            (IVec2::new(0, 1), Piece::T),
+        ];
+        assert_eq!(sorted(board.cells.clone()), sorted(expected));
+        assert_eq!(encode(&board).unwrap(), text);
+    }
+
+    #[test]
+    fn round_trips_every_piece() {
+        for piece in Piece::TETROMINOES {
+            for rotation in Rotation::ALL {
+                let mut active = piece.shape(rotation);
+                active.center = IVec2::new(4, 5);
+                let board = FumenBoard {
+                    cells: vec![
+                        (IVec2::new(0, 0), Piece::Gray),
+                        (IVec2::new(9, 0), piece),
+                        (IVec2::new(9, 22), Piece::Z),
+                    ],
+                    active: Some(active.clone()),
+                    queue: Piece::TETROMINOES
+                        .map(|piece| piece.shape(Rotation::Spawn))
+                        .to_vec(),
+                };
+                let decoded = decode(&encode(&board).unwrap()).unwrap();
+                assert_eq!(sorted(decoded.cells), sorted(board.cells.clone()));
+                assert_eq!(
+                    decoded.active.as_ref().map(cells),
+                    Some(cells(&active)),
+                    "{piece:?} {rotation:?}"
+                );
+                assert_eq!(letters(decoded.queue.iter()).unwrap(), "ILOZTJS");
+            }
+        }
+    }
+
+    #[test]
+    fn long_fumens_wrap_and_still_decode() {
+        let board = FumenBoard {
+            cells: (0..FIELD_TOP)
+                .flat_map(|y| (0..FIELD_WIDTH).map(move |x| IVec2::new(x, y)))
+                .filter(|cell| (cell.x + cell.y) % 3 != 0)
+                .map(|cell| (cell, Piece::TETROMINOES[(cell.x + cell.y) as usize % 7]))
+                .collect(),
+            ..Default::default()
+        };
+        let text = encode(&board).unwrap();
+        assert!(text.contains('?'));
+        let decoded = decode(&text).unwrap();
+        assert_eq!(sorted(decoded.cells), sorted(board.cells.clone()));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        assert!(matches!(decode("v110@vhAAgH"), Err(FumenError::Version)));
+        assert!(matches!(decode("v115@vh"), Err(FumenError::Corrupt(_))));
+    }
+
+    #[test]
+    fn groups_touching_cells_of_the_same_piece() {
+        let cells = [
+            (IVec2::new(0, 0), Piece::T),
+            (IVec2::new(1, 0), Piece::T),
+            (IVec2::new(2, 0), Piece::S),
+            (IVec2::new(3, 0), Piece::T),
+            (IVec2::new(3, 1), Piece::T),
+            (IVec2::new(4, 0), Piece::Gray),
+        ];
+        let mut groups = groups(&cells)
+            .into_iter()
+            .map(|(piece, mut cells)| {
+                cells.sort_by_key(|cell| (cell.y, cell.x));
+                (piece, cells)
+            })
+            .collect::<Vec<_>>();
+        groups.sort_by_key(|(_, cells)| (cells[0].y, cells[0].x));
+        assert_eq!(
+            groups,
+            vec![
+                (Piece::T, at(&[(0, 0), (1, 0)])),
+                (Piece::S, at(&[(2, 0)])),
+                (Piece::T, at(&[(3, 0), (3, 1)])),
+            ]
+        );
+    }
+
+    #[test]
+    fn floating_groups_are_not_supported() {
+        // ..ZZ......
+        // ...ZZ.....
+        // .IIII.....
+        // .......OO.
+        // LLL.X..OO.
+        // L.....XX..
+        let cells = [
+            (IVec2::new(0, 0), Piece::L),
+            (IVec2::new(0, 1), Piece::L),
+            (IVec2::new(1, 1), Piece::L),
+            (IVec2::new(2, 1), Piece::L),
+            (IVec2::new(1, 3), Piece::I),
+            (IVec2::new(2, 3), Piece::I),
+            (IVec2::new(3, 3), Piece::I),
+            (IVec2::new(4, 3), Piece::I),
+            (IVec2::new(3, 4), Piece::Z),
+            (IVec2::new(4, 4), Piece::Z),
+            (IVec2::new(2, 5), Piece::Z),
+            (IVec2::new(3, 5), Piece::Z),
+            (IVec2::new(4, 1), Piece::Gray),
+            (IVec2::new(6, 0), Piece::Gray),
+            (IVec2::new(7, 0), Piece::Gray),
+            (IVec2::new(7, 1), Piece::O),
+            (IVec2::new(8, 1), Piece::O),
+            (IVec2::new(7, 2), Piece::O),
+            (IVec2::new(8, 2), Piece::O),
+        ];
+        let gray = cells
+            .iter()
+            .filter(|(_, piece)| *piece == Piece::Gray)
+            .map(|(cell, _)| *cell)
+            .collect::<Vec<_>>();
+        let groups = groups(&cells);
+        let supported = supported(&groups, &gray);
+        let mut pieces = groups
+            .iter()
+            .zip(supported)
+            .map(|((piece, _), supported)| (piece.letter(), supported))
+            .collect::<Vec<_>>();
+        pieces.sort();
+        // the I hangs over the gap with the Z on top of it
+        assert_eq!(
+            pieces,
+            vec![('I', false), ('L', true), ('O', true), ('Z', false)]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    blocks::BlockSprites,
+    board::{spawn_static_block, Board, OnBoard, Shape, ToppedOut},
+    prelude::*,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<AddGarbage>().add_systems(
+        FixedUpdate,
+        apply_garbage
+            .before(crate::board::apply_gravity)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
+
+/// push this many rows of garbage up from the bottom of a board
+#[derive(Event)]
+pub struct AddGarbage {
+    pub board: Entity,
+    pub rows: i32,
+}
+
+/// a block that was put on the board as garbage
+#[derive(Component)]
+pub struct Garbage;
+
+fn apply_garbage(
+    mut events: EventReader<AddGarbage>,
+    mut boards: Query<&mut Board>,
+    mut shapes: Query<(&mut Shape, &OnBoard)>,
+    sprites: BlockSprites,
+    mut commands: Commands,
+    mut topped_out: EventWriter<ToppedOut>,
+    mut rng: ResMut<GameRng>,
+) {
+    // shapes are moved up once per board so several events in one tick can not pull them apart
+    let mut pending: Vec<(Entity, i32)> = Vec::new();
+    for AddGarbage { board, rows } in events.read() {
+        match pending.iter_mut().find(|(entity, _)| entity == board) {
+            Some((_, total)) => *total += rows,
+            None => pending.push((*board, *rows)),
+        }
+    }
+    for (entity, rows) in pending {
+        let Ok(mut board) = boards.get_mut(entity) else {
+            continue;
+        };
+        let Some(rows) = board.push_up(rows) else {
+            topped_out.write(ToppedOut { board: entity });
+            continue;
+        };
+        for (mut shape, on_board) in &mut shapes {
+            if on_board.0 == entity {
+                shape.center.y += rows;
+            }
+        }
+        for y in 0..rows {
+            let hole = rng.random_range(0..board.width());
+            spawn_garbage_row(
+                &mut commands,
+                entity,
+                &mut board,
+                sprites.sprite(GARBAGE_COLOR),
+                y,
+                hole,
+            );
+        }
+    }
+}
+
+/// garbage is grey so it never looks like part of a shape
+pub const GARBAGE_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+
+/// fill row `y` with garbage apart from the `hole` column
+/// garbage is not part of any shape so it never falls into holes once lines above it are cleared
+pub fn spawn_garbage_row(
+    commands: &mut Commands,
+    entity: Entity,
+    board: &mut Board,
+    sprite: Sprite,
+    y: i32,
+    hole: i32,
+) {
+    for x in (0..board.width()).filter(|x| *x != hole) {
+        let block = spawn_static_block(
+            commands,
+            entity,
+            board,
+            IVec2::new(x, y),
+            sprite.clone(),
+            None,
+        );
+        commands.entity(block).insert(Garbage);
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::{input::mouse::AccumulatedMouseScroll, prelude::*};
+
+use prelude::*;
+
+mod ai;
+mod blocks;
+mod bot;
+pub mod env;
+
+/// the whole game, add it next to `DefaultPlugins`
+pub fn plugin(app: &mut App) {
+    app.add_plugins((
+        leafwing_input_manager::prelude::InputManagerPlugin::<deck::PlayerInputs>::default(),
+        core_plugin,
+    ))
+    .insert_resource(bevy_pkv::PkvStore::new("Phox", "Tetris"))
+    .add_systems(Startup, spawn_camera)
+    .add_systems(Update, scroll_camera)
+    .add_plugins((
+        ui::plugin,
+        run::plugin,
+        dig::plugin,
+        replay::plugin,
+        ai::plugin,
+        net::plugin,
+        bot::plugin,
+        puzzle::plugin,
+        practice::plugin,
+        fumen::plugin,
+        save::plugin,
+    ))
+    .add_systems(Update, test_input);
+}
+
+/// the rules of the game without anything that needs a window, used on its own by `env`
+fn core_plugin(app: &mut App) {
+    app.add_plugins((
+        board::plugin,
+        deck::plugin,
+        blocks::plugin,
+        palette::plugin,
+        garbage::plugin,
+        mode::plugin,
+        player::plugin,
+        versus::plugin,
+    ))
+    .insert_resource(Time::<Fixed>::from_hz(mode::TICK_HZ))
+    .init_resource::<GameRng>()
+    .init_state::<GameState>()
+    .enable_state_scoped_entities::<GameState>()
+    // the rules send the player back to the menus when a game ends
+    .init_state::<ui::menus::Menu>();
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Transform::from_translation(Vec3::new(320., 0., 0.)),
+        IsDefaultUiCamera,
+    ));
+}
+
+fn scroll_camera(
+    scroll: Res<AccumulatedMouseScroll>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    for mut camera_transform in cameras.iter_mut() {
+        camera_transform.translation.y += scroll.delta.y * 25.;
+        camera_transform.translation.y = camera_transform.translation.y.clamp(0.0, 500.0);
+    }
+}
+
+mod board;
+mod deck;
+mod dig;
+mod fumen;
+mod garbage;
+mod mode;
+mod net;
+mod palette;
+mod player;
+mod practice;
+mod puzzle;
+mod replay;
+mod run;
+mod save;
+mod ui;
+mod versus;
+
+pub mod prelude {
+    use bevy::prelude::*;
+
+    pub(crate) use super::GameState;
+
+    #[derive(Component, Default, Deref, DerefMut)]
+    pub struct Score(pub i32);
+
+    /// every random choice made while playing comes from here so a game can be replayed from its seed
+    #[derive(Resource, Clone, Deref, DerefMut)]
+    pub struct GameRng(pub rand::rngs::StdRng);
+
+    impl GameRng {
+        pub fn seeded(seed: u64) -> Self {
+            GameRng(rand::SeedableRng::seed_from_u64(seed))
+        }
+
+        /// carry on from a new seed drawn from this rng, the seed is all that is needed to save its state
+        pub fn reseed(&mut self) -> u64 {
+            let seed = rand::Rng::random(&mut self.0);
+            *self = GameRng::seeded(seed);
+            seed
+        }
+    }
+
+    impl Default for GameRng {
+        fn default() -> Self {
+            GameRng::seeded(rand::random())
+        }
+    }
+
+    #[derive(strum_macros::AsRefStr)]
+    pub enum DataKeys {
+        UiPalette,
+        FontSize,
+        CustomDeck,
+        SelectedDeck,
+        BlockPalette,
+        BlockSkin,
+        ModeRecords,
+        PuzzleRecords,
+        SavedGame,
+    }
+}
+
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum GameState {
+    #[default]
+    InMenu,
+    Playing,
+}
+
+fn test_input(
+    input: Res<ButtonInput<KeyCode>>,
+    leafwing: Res<leafwing_input_manager::prelude::ActionState<deck::PlayerInputs>>,
+) {
+    if input.just_pressed(KeyCode::KeyA) {
+        info!("Key A pressed");
+        if leafwing.just_pressed(&deck::PlayerInputs::MoveLeft) {
+            info!("Move Left pressed");
+        } else {
+            info!("leafwing inputs: {:?}", leafwing.get_pressed());
+        }
+    }
+}
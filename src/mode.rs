@@ -0,0 +1,337 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_pkv::PkvStore;
+
+use crate::{
+    ai::AiPlayer,
+    board::{GameClock, LinesCleared, ToppedOut, STEP_TICKS},
+    player::Player,
+    prelude::*,
+    ui::menus::Menu,
+};
+
+/// fixed ticks per second, blocks fall every `STEP_TICKS` of these
+pub const TICK_HZ: f64 = 30.;
+/// results kept for each mode
+const KEPT_RESULTS: usize = 10;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<GameMode>()
+        .init_resource::<ModeProgress>()
+        .init_resource::<ModeRecords>()
+        .add_systems(OnEnter(GameState::Playing), start_mode)
+        .add_systems(FixedUpdate, tick_mode.run_if(in_state(GameState::Playing)))
+        .add_systems(
+            FixedLast,
+            (count_lines, lose_mode, finish_mode)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, save_records.run_if(resource_changed::<ModeRecords>));
+}
+
+/// the rules the current game is played by
+#[derive(
+    Resource, Default, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+pub enum GameMode {
+    /// play until the board fills up
+    #[default]
+    Endless,
+    /// clear 40 lines as fast as possible
+    Sprint,
+    /// score as many points as possible in 2 minutes
+    Ultra,
+    /// clear 150 lines while the game speeds up every 10
+    Marathon,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Endless,
+        GameMode::Sprint,
+        GameMode::Ultra,
+        GameMode::Marathon,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::Sprint => "Sprint",
+            GameMode::Ultra => "Ultra",
+            GameMode::Marathon => "Marathon",
+        }
+    }
+
+    /// lines that finish the game
+    pub fn line_goal(self) -> Option<i32> {
+        match self {
+            GameMode::Sprint => Some(40),
+            GameMode::Marathon => Some(150),
+            GameMode::Endless | GameMode::Ultra => None,
+        }
+    }
+
+    /// seconds the game lasts
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::Ultra => Some(120.),
+            GameMode::Endless | GameMode::Sprint | GameMode::Marathon => None,
+        }
+    }
+
+    /// lines needed for each level up
+    pub fn lines_per_level(self) -> Option<i32> {
+        match self {
+            GameMode::Marathon => Some(10),
+            GameMode::Endless | GameMode::Sprint | GameMode::Ultra => None,
+        }
+    }
+
+    /// true if a lower time is the better result, otherwise the higher score is
+    pub fn timed(self) -> bool {
+        matches!(self, GameMode::Sprint)
+    }
+
+    /// lines of text shown next to the board
+    pub fn hud(self, progress: &ModeProgress, score: i32) -> Vec<String> {
+        let mut hud = Vec::new();
+        match self.time_limit() {
+            Some(limit) => hud.push(format!("Time {:.1}", (limit - progress.time).max(0.))),
+            None => hud.push(format!("Time {:.1}", progress.time)),
+        }
+        match self.line_goal() {
+            Some(goal) => hud.push(format!("Lines {} of {goal}", progress.lines)),
+            None => hud.push(format!("Lines {}", progress.lines)),
+        }
+        if self.lines_per_level().is_some() {
+            hud.push(format!("Level {}", progress.level));
+        }
+        if !self.timed() {
+            hud.push(format!("Score {score}"));
+        }
+        hud
+    }
+
+    /// true once the goal of the mode has been reached
+    pub fn finished(self, progress: &ModeProgress) -> bool {
+        if let Some(goal) = self.line_goal() {
+            if progress.lines >= goal {
+                return true;
+            }
+        }
+        if let Some(limit) = self.time_limit() {
+            if progress.time >= limit {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// how far through the current mode the game is
+#[derive(Resource, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModeProgress {
+    pub lines: i32,
+    pub level: u32,
+    /// seconds of play, time in menus is not counted
+    pub time: f32,
+}
+
+impl Default for ModeProgress {
+    fn default() -> Self {
+        ModeProgress {
+            lines: 0,
+            level: 1,
+            time: 0.,
+        }
+    }
+}
+
+/// the end of one game
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModeResult {
+    pub mode: GameMode,
+    pub score: i32,
+    pub lines: i32,
+    pub time: f32,
+    /// false if the board filled up before the goal was reached
+    pub completed: bool,
+}
+
+impl ModeResult {
+    fn better_than(&self, other: &ModeResult) -> bool {
+        if self.completed != other.completed {
+            return self.completed;
+        }
+        if self.mode.timed() && self.completed {
+            self.time < other.time
+        } else {
+            self.score > other.score
+        }
+    }
+
+    /// short description for menus
+    pub fn label(&self) -> String {
+        if self.mode.timed() && self.completed {
+            format!("{:.1}s", self.time)
+        } else {
+            format!("{} points", self.score)
+        }
+    }
+}
+
+/// the last few results of every mode
+#[derive(Resource, Default, serde::Serialize, serde::Deserialize)]
+pub struct ModeRecords {
+    results: Vec<ModeResult>,
+}
+
+impl ModeRecords {
+    pub fn results(&self, mode: GameMode) -> impl Iterator<Item = &ModeResult> {
+        self.results.iter().filter(move |r| r.mode == mode)
+    }
+
+    pub fn best(&self, mode: GameMode) -> Option<&ModeResult> {
+        self.results(mode)
+            .reduce(|best, r| if r.better_than(best) { r } else { best })
+    }
+
+    /// add a result, dropping the oldest one of the same mode if there are too many
+    pub fn record(&mut self, result: ModeResult) {
+        if self.results(result.mode).count() >= KEPT_RESULTS {
+            if let Some(oldest) = self.results.iter().position(|r| r.mode == result.mode) {
+                self.results.remove(oldest);
+            }
+        }
+        self.results.push(result);
+    }
+}
+
+impl FromWorld for ModeRecords {
+    fn from_world(world: &mut World) -> Self {
+        let store = world.resource::<PkvStore>();
+        if let Ok(records) = store.get(DataKeys::ModeRecords) {
+            records
+        } else {
+            ModeRecords::default()
+        }
+    }
+}
+
+fn save_records(mut store: ResMut<PkvStore>, records: Res<ModeRecords>) {
+    if let Err(e) = store.set(DataKeys::ModeRecords, &*records) {
+        error!("Failed to save mode records: {e:?}");
+    };
+}
+
+pub(crate) fn start_mode(mut progress: ResMut<ModeProgress>) {
+    *progress = ModeProgress::default();
+}
+
+fn count_lines(
+    mode: Res<GameMode>,
+    mut events: EventReader<LinesCleared>,
+    mut progress: ResMut<ModeProgress>,
+    mut clock: ResMut<GameClock>,
+) {
+    for LinesCleared { lines, .. } in events.read() {
+        progress.lines += lines;
+    }
+    let Some(per_level) = mode.lines_per_level() else {
+        return;
+    };
+    let level = 1 + (progress.lines / per_level) as u32;
+    if level != progress.level {
+        progress.level = level;
+        info!("Level {level}");
+        // every level makes the blocks fall a tick sooner
+        clock.step_ticks = STEP_TICKS.saturating_sub(level - 1).max(1);
+    }
+}
+
+fn tick_mode(time: Res<Time>, mut progress: ResMut<ModeProgress>) {
+    progress.time += time.delta_secs();
+}
+
+fn finish_mode(
+    mode: Res<GameMode>,
+    progress: Res<ModeProgress>,
+    players: Query<(&Score, Has<AiPlayer>), With<Player>>,
+    mut records: ResMut<ModeRecords>,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<Menu>>,
+    playback: Option<Res<crate::replay::Playback>>,
+) {
+    if !mode.finished(&progress) {
+        return;
+    }
+    info!("{} complete", mode.name());
+    state.set(GameState::InMenu);
+    menu.set(Menu::Main);
+    // watching a replay does not count as playing
+    let Some(score) = solo_score(&players).filter(|_| playback.is_none()) else {
+        return;
+    };
+    records.record(ModeResult {
+        mode: *mode,
+        score,
+        lines: progress.lines,
+        time: progress.time,
+        completed: true,
+    });
+}
+
+/// games that are played by other rules than the selected `GameMode`
+#[derive(SystemParam)]
+pub struct OtherModes<'w> {
+    pub run: Option<Res<'w, crate::run::Run>>,
+    pub dig: Option<Res<'w, crate::dig::Dig>>,
+    pub puzzle: Option<Res<'w, crate::puzzle::ActivePuzzle>>,
+    pub playback: Option<Res<'w, crate::replay::Playback>>,
+    pub practice: Option<Res<'w, crate::practice::Practice>>,
+}
+
+impl OtherModes<'_> {
+    pub fn any(&self) -> bool {
+        self.run.is_some()
+            || self.dig.is_some()
+            || self.puzzle.is_some()
+            || self.playback.is_some()
+            || self.practice.is_some()
+    }
+}
+
+fn lose_mode(
+    mode: Res<GameMode>,
+    mut events: EventReader<ToppedOut>,
+    progress: Res<ModeProgress>,
+    players: Query<(&Score, Has<AiPlayer>), With<Player>>,
+    mut records: ResMut<ModeRecords>,
+    other_modes: OtherModes,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    // runs, digs and puzzles have their own goals and practice can take shapes back
+    if other_modes.any() {
+        return;
+    }
+    let Some(score) = solo_score(&players) else {
+        return;
+    };
+    records.record(ModeResult {
+        mode: *mode,
+        score,
+        lines: progress.lines,
+        time: progress.time,
+        completed: false,
+    });
+}
+
+/// only a single person playing alone can set a record
+fn solo_score(players: &Query<(&Score, Has<AiPlayer>), With<Player>>) -> Option<i32> {
+    match players.single() {
+        Ok((score, false)) => Some(score.0),
+        _ => None,
+    }
+}
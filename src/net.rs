@@ -0,0 +1,538 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::ErrorKind,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::{RunFixedMainLoop, RunFixedMainLoopSystem},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+
+use crate::{
+    board::{BlockState, Board, GameClock},
+    deck::{Deck, ReadInputs, TickInputs},
+    player::{Player, PlayerKind, PlayerSetup},
+    prelude::*,
+    ui::menus::Menu,
+    versus::AttackSent,
+};
+
+mod protocol;
+
+pub use protocol::{
+    parse_address, Connection, NetError, NetMessage, NetReceiver, NetSender, DEFAULT_PORT,
+};
+
+/// ticks between pressing a key and it taking effect, time for it to reach the other game
+const INPUT_DELAY: u64 = 3;
+/// how long to wait on the other game before giving up on it
+const NET_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<NetLobby>()
+        .add_systems(
+            Update,
+            (
+                accept_player.run_if(resource_exists::<NetListener>),
+                await_hello.run_if(resource_exists::<PendingJoin>),
+            )
+                .run_if(in_state(GameState::InMenu)),
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            seed_net_game
+                .after(crate::replay::start_game)
+                .before(crate::player::spawn_players)
+                .run_if(resource_exists::<NetGame>),
+        )
+        .add_systems(
+            PreUpdate,
+            receive_net.run_if(resource_exists::<NetGame>.and(in_state(GameState::Playing))),
+        )
+        .configure_sets(
+            RunFixedMainLoop,
+            RunFixedMainLoopSystem::FixedMainLoop.run_if(remote_inputs_ready),
+        )
+        .add_systems(
+            FixedFirst,
+            exchange_inputs
+                .after(ReadInputs)
+                .after(crate::board::advance_clock)
+                .run_if(resource_exists::<NetGame>.and(in_state(GameState::Playing))),
+        )
+        .add_systems(OnExit(GameState::Playing), leave_net_game);
+}
+
+/// what the host and join screens show
+#[derive(Resource, Default)]
+pub struct NetLobby {
+    /// the port to host on or the address to join, as typed
+    pub address: String,
+    /// what is happening or the last thing that went wrong
+    pub status: Option<String>,
+}
+
+/// waiting for someone to join
+#[derive(Resource)]
+pub struct NetListener(TcpListener);
+
+/// connected to a host but the game has not started yet, taken once it does
+#[derive(Resource)]
+pub struct PendingJoin(Option<Connection>);
+
+/// a game against someone on another machine, both games run every tick with the same inputs
+#[derive(Resource)]
+pub struct NetGame {
+    sender: NetSender,
+    receiver: NetReceiver,
+    /// index of the player on this machine
+    local: usize,
+    seed: u64,
+    /// the first tick of the game, nothing is sent for the ticks before the input delay
+    start: Option<u64>,
+    /// local inputs waiting out the input delay and the tick they are used on
+    delayed: VecDeque<(u64, TickInputs)>,
+    /// inputs from the other game by tick
+    remote: BTreeMap<u64, TickInputs>,
+    /// what this game looked like on ticks the other game has not reported yet
+    checkpoints: BTreeMap<u64, Checkpoint>,
+    /// what the other game looked like on ticks this game has not reached yet
+    remote_checkpoints: BTreeMap<u64, Checkpoint>,
+    /// the game ends once nothing has arrived for `NET_TIMEOUT`
+    last_heard: Instant,
+    /// the deck the joiner had before the host's replaced it, put back once the game ends
+    own_deck: Option<Deck>,
+}
+
+#[derive(PartialEq)]
+struct Checkpoint {
+    hash: u64,
+    /// garbage sent by the player on the other machine
+    attacks: Vec<i32>,
+}
+
+impl NetGame {
+    fn new(connection: Connection, local: usize, seed: u64) -> Result<Self, NetError> {
+        let (sender, receiver) = connection.read_in_background()?;
+        Ok(NetGame {
+            sender,
+            receiver,
+            local,
+            seed,
+            start: None,
+            delayed: VecDeque::new(),
+            remote: BTreeMap::new(),
+            checkpoints: BTreeMap::new(),
+            remote_checkpoints: BTreeMap::new(),
+            last_heard: Instant::now(),
+            own_deck: None,
+        })
+    }
+
+    fn remote_index(&self) -> usize {
+        1 - self.local
+    }
+
+    fn receive(&mut self, message: NetMessage) -> Result<(), NetError> {
+        match message {
+            NetMessage::Tick {
+                tick,
+                inputs,
+                checked,
+                hash,
+                attacks,
+            } => {
+                self.remote.insert(tick, inputs);
+                self.remote_checkpoints
+                    .insert(checked, Checkpoint { hash, attacks });
+                self.check(checked)
+            }
+            NetMessage::Bye => Err(NetError::Closed),
+            NetMessage::Hello { .. } => Ok(()),
+        }
+    }
+
+    /// compare both games on `tick` once both have got that far, whichever one is ahead
+    fn check(&mut self, tick: u64) -> Result<(), NetError> {
+        let (Some(local), Some(remote)) = (
+            self.checkpoints.get(&tick),
+            self.remote_checkpoints.get(&tick),
+        ) else {
+            return Ok(());
+        };
+        if local != remote {
+            return Err(NetError::Desync { tick });
+        }
+        self.checkpoints.remove(&tick);
+        self.remote_checkpoints.remove(&tick);
+        Ok(())
+    }
+
+    /// let the other game know this one is done and put back what it replaced
+    fn close(&mut self, deck: &mut Deck) {
+        self.sender.close();
+        if let Some(own) = self.own_deck.take() {
+            *deck = own;
+        }
+    }
+}
+
+/// what is needed to drop out of an online game that went wrong
+#[derive(SystemParam)]
+struct NetExit<'w, 's> {
+    commands: Commands<'w, 's>,
+    deck: ResMut<'w, Deck>,
+    lobby: ResMut<'w, NetLobby>,
+    state: ResMut<'w, NextState<GameState>>,
+    menu: ResMut<'w, NextState<Menu>>,
+}
+
+impl NetExit<'_, '_> {
+    fn end(&mut self, net: &mut NetGame, e: NetError) {
+        error!("Online game ended: {e}");
+        net.close(&mut self.deck);
+        self.lobby.status = Some(e.to_string());
+        self.commands.remove_resource::<NetGame>();
+        self.state.set(GameState::InMenu);
+        self.menu.set(if net.local == 0 {
+            Menu::Host
+        } else {
+            Menu::Join
+        });
+    }
+}
+
+/// listen for someone to join on `port`
+pub fn host(commands: &mut Commands, lobby: &mut NetLobby) {
+    let port = match lobby.address.trim() {
+        "" => DEFAULT_PORT,
+        port => match port.parse() {
+            Ok(port) => port,
+            Err(_) => {
+                lobby.status = Some(NetError::InvalidAddress(port.to_string()).to_string());
+                return;
+            }
+        },
+    };
+    let listener = TcpListener::bind(("0.0.0.0", port)).and_then(|listener| {
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    });
+    match listener {
+        Ok(listener) => {
+            info!("Hosting on port {port}");
+            lobby.status = Some(format!("Waiting for a player on port {port}"));
+            commands.insert_resource(NetListener(listener));
+        }
+        Err(e) => {
+            error!("Failed to host on port {port}: {e}");
+            lobby.status = Some(NetError::from(e).to_string());
+        }
+    }
+}
+
+/// connect to a host, the game starts once it says hello
+pub fn join(commands: &mut Commands, lobby: &mut NetLobby) {
+    match connect(&lobby.address) {
+        Ok(connection) => {
+            lobby.status = Some("Waiting for the host".into());
+            commands.insert_resource(PendingJoin(Some(connection)));
+        }
+        Err(e) => {
+            error!("Failed to join {}: {e}", lobby.address);
+            lobby.status = Some(e.to_string());
+        }
+    }
+}
+
+fn connect(address: &str) -> Result<Connection, NetError> {
+    let (host, port) = parse_address(address)?;
+    let address = (host.as_str(), port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| NetError::InvalidAddress(address.to_string()))?;
+    Connection::new(TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?)
+}
+
+/// stop hosting or joining
+pub fn close_lobby(mut commands: Commands) {
+    commands.remove_resource::<NetListener>();
+    commands.remove_resource::<PendingJoin>();
+}
+
+fn start_net_game(
+    commands: &mut Commands,
+    game: NetGame,
+    setup: PlayerSetup,
+    state: &mut NextState<GameState>,
+) {
+    commands.remove_resource::<crate::run::Run>();
+    commands.remove_resource::<crate::dig::Dig>();
+    commands.insert_resource(crate::mode::GameMode::Endless);
+    commands.insert_resource(setup);
+    commands.insert_resource(game);
+    state.set(GameState::Playing);
+}
+
+fn accept_player(
+    mut commands: Commands,
+    listener: Res<NetListener>,
+    deck: Res<Deck>,
+    mut lobby: ResMut<NetLobby>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let stream = match listener.0.accept() {
+        Ok((stream, address)) => {
+            info!("{address} joined");
+            stream
+        }
+        Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+        Err(e) => {
+            lobby.status = Some(NetError::from(e).to_string());
+            commands.remove_resource::<NetListener>();
+            return;
+        }
+    };
+    commands.remove_resource::<NetListener>();
+    let seed = rand::random();
+    let setup = PlayerSetup::versus(PlayerKind::Human, PlayerKind::Remote);
+    let hello = NetMessage::Hello {
+        game_version: env!("CARGO_PKG_VERSION").to_string(),
+        seed,
+        deck: deck.clone(),
+        width: setup.width,
+        hight: setup.hight,
+    };
+    let connected = Connection::new(stream).and_then(|mut connection| {
+        connection.send(&hello)?;
+        Ok(connection)
+    });
+    match connected.and_then(|connection| NetGame::new(connection, 0, seed)) {
+        Ok(game) => {
+            lobby.status = None;
+            start_net_game(&mut commands, game, setup, &mut state);
+        }
+        Err(e) => {
+            error!("Failed to start the game: {e}");
+            lobby.status = Some(e.to_string());
+        }
+    }
+}
+
+fn await_hello(
+    mut commands: Commands,
+    mut pending: ResMut<PendingJoin>,
+    mut deck: ResMut<Deck>,
+    mut lobby: ResMut<NetLobby>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let Some(connection) = pending.0.as_mut() else {
+        return;
+    };
+    let message = match connection.recv(Duration::from_millis(1)) {
+        Ok(Some(message)) => message,
+        Ok(None) => return,
+        Err(e) => {
+            lobby.status = Some(e.to_string());
+            commands.remove_resource::<PendingJoin>();
+            return;
+        }
+    };
+    let NetMessage::Hello {
+        game_version,
+        seed,
+        deck: host_deck,
+        width,
+        hight,
+    } = message
+    else {
+        return;
+    };
+    commands.remove_resource::<PendingJoin>();
+    if game_version != env!("CARGO_PKG_VERSION") {
+        lobby.status = Some(NetError::Incompatible { game_version }.to_string());
+        return;
+    }
+    let Some(connection) = pending.0.take() else {
+        return;
+    };
+    let mut game = match NetGame::new(connection, 1, seed) {
+        Ok(game) => game,
+        Err(e) => {
+            error!("Failed to start the game: {e}");
+            lobby.status = Some(e.to_string());
+            return;
+        }
+    };
+    lobby.status = None;
+    game.own_deck = Some(std::mem::replace(&mut *deck, host_deck));
+    let setup = PlayerSetup {
+        width,
+        hight,
+        ..PlayerSetup::versus(PlayerKind::Remote, PlayerKind::Human)
+    };
+    start_net_game(&mut commands, game, setup, &mut state);
+}
+
+/// both games have to draw the same shapes and garbage holes
+fn seed_net_game(net: Res<NetGame>, mut rng: ResMut<GameRng>) {
+    *rng = GameRng::seeded(net.seed);
+}
+
+/// hash of every board and score, entities differ between the games so only filled cells count
+fn board_hash<'a>(players: impl Iterator<Item = (&'a Player, &'a Board, &'a Score)>) -> u64 {
+    let mut players = players.collect::<Vec<_>>();
+    players.sort_by_key(|(player, _, _)| player.index);
+    let mut hasher = DefaultHasher::new();
+    for (player, board, score) in players {
+        player.index.hash(&mut hasher);
+        score.0.hash(&mut hasher);
+        for y in 0..board.hight() {
+            for x in 0..board.width() {
+                let filled = matches!(board.get(IVec2::new(x, y)), BlockState::Contains(_));
+                filled.hash(&mut hasher);
+            }
+        }
+    }
+    hasher.finish()
+}
+
+/// take in everything the other game has sent, it is given up on if it goes quiet for too long
+fn receive_net(mut net: ResMut<NetGame>, mut exit: NetExit) {
+    loop {
+        let received = match net.receiver.try_recv() {
+            Ok(Some(message)) => {
+                net.last_heard = Instant::now();
+                net.receive(message)
+            }
+            Ok(None) if net.last_heard.elapsed() > NET_TIMEOUT => Err(NetError::TimedOut),
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = received {
+            exit.end(&mut net, e);
+            return;
+        }
+    }
+}
+
+/// the fixed ticks this frame only run once the other games inputs for all of them are in,
+/// until then the game stands still rather than holding up the frame
+fn remote_inputs_ready(
+    net: Option<Res<NetGame>>,
+    clock: Res<GameClock>,
+    fixed: Res<Time<Fixed>>,
+    time: Res<Time<Virtual>>,
+) -> bool {
+    let Some(start) = net.as_ref().and_then(|net| net.start) else {
+        return true;
+    };
+    // the same sum `Time<Fixed>` makes to decide how many ticks to run
+    let ticks =
+        ((fixed.overstep() + time.delta()).as_secs_f64() / fixed.timestep().as_secs_f64()) as u64;
+    (clock.tick + 1..=clock.tick + ticks)
+        .filter(|tick| *tick >= start + INPUT_DELAY)
+        .all(|tick| {
+            net.as_ref()
+                .is_some_and(|net| net.remote.contains_key(&tick))
+        })
+}
+
+/// send this tick's local inputs and use the other games, `remote_inputs_ready` made sure they are here
+fn exchange_inputs(
+    mut net: ResMut<NetGame>,
+    clock: Res<GameClock>,
+    mut players: Query<(&Player, &Board, &Score, &mut TickInputs)>,
+    mut sent: EventReader<AttackSent>,
+    mut exit: NetExit,
+) {
+    let tick = clock.tick;
+    let start = *net.start.get_or_insert(tick);
+    // the state the last tick left behind
+    let checked = tick - 1;
+    let hash = board_hash(
+        players
+            .iter()
+            .map(|(player, board, score, _)| (player, board, score)),
+    );
+    let mut attacks = vec![Vec::new(); players.iter().count()];
+    for AttackSent { from, rows } in sent.read() {
+        if let Some(sent) = players
+            .get(*from)
+            .ok()
+            .and_then(|(player, ..)| attacks.get_mut(player.index))
+        {
+            sent.push(*rows);
+        }
+    }
+    let local = net.local;
+    let Some(local_inputs) = players
+        .iter()
+        .find(|(player, ..)| player.index == local)
+        .map(|(.., inputs)| *inputs)
+    else {
+        return;
+    };
+    let message = NetMessage::Tick {
+        tick: tick + INPUT_DELAY,
+        inputs: local_inputs,
+        checked,
+        hash,
+        attacks: attacks.get(local).cloned().unwrap_or_default(),
+    };
+    let remote_attacks = attacks.get(net.remote_index()).cloned().unwrap_or_default();
+    net.checkpoints.insert(
+        checked,
+        Checkpoint {
+            hash,
+            attacks: remote_attacks,
+        },
+    );
+    net.delayed.push_back((tick + INPUT_DELAY, local_inputs));
+
+    let remote_inputs = net
+        .sender
+        .send(&message)
+        .and_then(|_| net.check(checked))
+        .and_then(|_| {
+            if tick < start + INPUT_DELAY {
+                Ok(TickInputs::default())
+            } else {
+                net.remote.remove(&tick).ok_or(NetError::Missing { tick })
+            }
+        });
+    let remote_inputs = match remote_inputs {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            exit.end(&mut net, e);
+            return;
+        }
+    };
+    let local_inputs = match net.delayed.front() {
+        Some(&(at, inputs)) if at == tick => {
+            net.delayed.pop_front();
+            inputs
+        }
+        _ => TickInputs::default(),
+    };
+    for (player, _, _, mut inputs) in &mut players {
+        *inputs = if player.index == local {
+            local_inputs
+        } else {
+            remote_inputs
+        };
+    }
+}
+
+fn leave_net_game(mut commands: Commands, net: Option<ResMut<NetGame>>, mut deck: ResMut<Deck>) {
+    let Some(mut net) = net else {
+        return;
+    };
+    net.close(&mut deck);
+    commands.remove_resource::<NetGame>();
+}
@@ -0,0 +1,210 @@
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{Shutdown, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use crate::deck::{Deck, TickInputs};
+
+/// port used when an address does not give one
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// one line of RON sent between the two games
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum NetMessage {
+    /// sent by the host once someone joins, everything needed to start the same game
+    Hello {
+        game_version: String,
+        seed: u64,
+        deck: Deck,
+        width: i32,
+        hight: i32,
+    },
+    /// the senders inputs for `tick`, along with what their game looked like at `checked`
+    Tick {
+        tick: u64,
+        inputs: TickInputs,
+        checked: u64,
+        hash: u64,
+        /// garbage the sender attacked with on `checked`
+        attacks: Vec<i32>,
+    },
+    /// the other game left
+    Bye,
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Write(ron::Error),
+    InvalidAddress(String),
+    Incompatible { game_version: String },
+    Closed,
+    TimedOut,
+    Desync { tick: u64 },
+    Missing { tick: u64 },
+}
+
+impl std::fmt::Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Io(e) => write!(f, "connection failed: {e}"),
+            NetError::Ron(e) => write!(f, "failed to read message: {e}"),
+            NetError::Write(e) => write!(f, "failed to write message: {e}"),
+            NetError::InvalidAddress(address) => write!(f, "{address:?} is not a valid address"),
+            NetError::Incompatible { game_version } => write!(
+                f,
+                "the host is on version {game_version} but this is version {}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            NetError::Closed => write!(f, "the other player left"),
+            NetError::TimedOut => write!(f, "the other player stopped responding"),
+            NetError::Desync { tick } => write!(f, "the games stopped matching on tick {tick}"),
+            NetError::Missing { tick } => {
+                write!(f, "the other players inputs for tick {tick} never arrived")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+impl From<std::io::Error> for NetError {
+    fn from(value: std::io::Error) -> Self {
+        NetError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for NetError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        NetError::Ron(value)
+    }
+}
+
+impl From<ron::Error> for NetError {
+    fn from(value: ron::Error) -> Self {
+        NetError::Write(value)
+    }
+}
+
+/// a tcp stream carrying one message per line
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    /// the start of a line that has not fully arrived yet
+    partial: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, NetError> {
+        stream.set_nonblocking(false)?;
+        // inputs are tiny and needed right away
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            partial: Vec::new(),
+        })
+    }
+
+    pub fn send(&mut self, message: &NetMessage) -> Result<(), NetError> {
+        write_message(self.reader.get_mut(), message)
+    }
+
+    /// wait up to `timeout` for the next message, `None` if it has not arrived yet
+    pub fn recv(&mut self, timeout: Duration) -> Result<Option<NetMessage>, NetError> {
+        self.reader.get_ref().set_read_timeout(Some(timeout))?;
+        self.read_message()
+    }
+
+    /// read messages on their own thread from now on so waiting for them never holds up a frame
+    /// the thread stops once the connection is closed from either end
+    pub fn read_in_background(mut self) -> Result<(NetSender, NetReceiver), NetError> {
+        let stream = self.reader.get_ref().try_clone()?;
+        self.reader.get_ref().set_read_timeout(None)?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("net receive".into())
+            .spawn(move || loop {
+                let message = match self.read_message() {
+                    Ok(Some(message)) => Ok(message),
+                    Ok(None) => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    break;
+                }
+            })?;
+        Ok((NetSender(stream), NetReceiver(Mutex::new(receiver))))
+    }
+
+    fn read_message(&mut self) -> Result<Option<NetMessage>, NetError> {
+        match self.reader.read_until(b'\n', &mut self.partial) {
+            Ok(_) if self.partial.ends_with(b"\n") => {
+                let line = std::mem::take(&mut self.partial);
+                Ok(Some(ron::de::from_bytes(&line)?))
+            }
+            // the stream ended part way through a line or before one started
+            Ok(_) => Err(NetError::Closed),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn write_message(stream: &mut TcpStream, message: &NetMessage) -> Result<(), NetError> {
+    let mut line = ron::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// the writing half of a connection that is read by a `NetReceiver`
+pub struct NetSender(TcpStream);
+
+impl NetSender {
+    pub fn send(&mut self, message: &NetMessage) -> Result<(), NetError> {
+        write_message(&mut self.0, message)
+    }
+
+    /// say goodbye and shut the connection, which also ends the thread reading it
+    pub fn close(&mut self) {
+        // the other game may already be gone
+        let _ = self.send(&NetMessage::Bye);
+        let _ = self.0.shutdown(Shutdown::Both);
+    }
+}
+
+/// messages read by the thread `Connection::read_in_background` started
+pub struct NetReceiver(Mutex<Receiver<Result<NetMessage, NetError>>>);
+
+impl NetReceiver {
+    /// the next message if one has arrived, never waits
+    pub fn try_recv(&mut self) -> Result<Option<NetMessage>, NetError> {
+        let receiver = self.0.get_mut().unwrap_or_else(PoisonError::into_inner);
+        match receiver.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(NetError::Closed),
+        }
+    }
+}
+
+/// `host port` or just `host`, spaces are used since the menu font has no colon
+pub fn parse_address(text: &str) -> Result<(String, u16), NetError> {
+    let mut parts = text.split_whitespace();
+    let invalid = || NetError::InvalidAddress(text.to_string());
+    let host = parts.next().ok_or_else(invalid)?;
+    let port = match parts.next() {
+        Some(port) => port.parse().map_err(|_| invalid())?,
+        None => DEFAULT_PORT,
+    };
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok((host.to_string(), port))
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+
+use crate::{board::Shape, deck::Deck, prelude::*};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<BlockPalette>().add_systems(
+        Update,
+        save_block_palette.run_if(resource_changed::<BlockPalette>),
+    );
+}
+
+/// colours for each kind of shape, shapes that are not in the palette keep the colour from their deck
+#[derive(Resource, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BlockPalette {
+    colors: Vec<(Vec<(i32, i32)>, Color)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PalettePreset {
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl PalettePreset {
+    pub const ALL: [PalettePreset; 4] = [
+        PalettePreset::Standard,
+        PalettePreset::Deuteranopia,
+        PalettePreset::Protanopia,
+        PalettePreset::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PalettePreset::Standard => "Standard",
+            PalettePreset::Deuteranopia => "Deuteranopia",
+            PalettePreset::Protanopia => "Protanopia",
+            PalettePreset::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// colours for the classic shapes in deck order I Z O S T J L
+    fn colors(self) -> Option<[Srgba; 7]> {
+        let hex = match self {
+            PalettePreset::Standard => return None,
+            PalettePreset::Deuteranopia => [
+                "#56B4E9", "#D55E00", "#F0E442", "#009E73", "#CC79A7", "#0072B2", "#E69F00",
+            ],
+            PalettePreset::Protanopia => [
+                "#66CCEE", "#EE6677", "#CCBB44", "#228833", "#AA3377", "#4477AA", "#BBBBBB",
+            ],
+            PalettePreset::Tritanopia => [
+                "#33BBEE", "#CC3311", "#EE7733", "#009988", "#EE3377", "#0077BB", "#BBBBBB",
+            ],
+        };
+        Some(hex.map(|hex| Srgba::hex(hex).expect("preset colours are valid hex")))
+    }
+}
+
+impl BlockPalette {
+    pub fn preset(preset: PalettePreset) -> Self {
+        let mut palette = BlockPalette { colors: Vec::new() };
+        let Some(colors) = preset.colors() else {
+            return palette;
+        };
+        for (shape, color) in Deck::classic().shapes().iter().zip(colors) {
+            palette.set(shape, color.into());
+        }
+        palette
+    }
+
+    pub fn get(&self, shape: &Shape) -> Option<Color> {
+        let key = shape_key(&shape.blocks);
+        self.colors
+            .iter()
+            .find(|(other, _)| *other == key)
+            .map(|(_, color)| *color)
+    }
+
+    pub fn set(&mut self, shape: &Shape, color: Color) {
+        let key = shape_key(&shape.blocks);
+        if let Some((_, old)) = self.colors.iter_mut().find(|(other, _)| *other == key) {
+            *old = color;
+        } else {
+            self.colors.push((key, color));
+        }
+    }
+
+    /// the colour a shape should be drawn with
+    pub fn color(&self, shape: &Shape) -> Color {
+        self.get(shape).unwrap_or(shape.color)
+    }
+}
+
+/// the same key for every rotation and position of a shape
+pub(crate) fn shape_key(blocks: &[IVec2]) -> Vec<(i32, i32)> {
+    let mut blocks = blocks.to_vec();
+    let mut best: Option<Vec<(i32, i32)>> = None;
+    for _ in 0..4 {
+        let min = blocks
+            .iter()
+            .copied()
+            .reduce(IVec2::min)
+            .unwrap_or_default();
+        let mut key = blocks
+            .iter()
+            .map(|block| (block.x - min.x, block.y - min.y))
+            .collect::<Vec<_>>();
+        key.sort();
+        if best.as_ref().is_none_or(|best| key < *best) {
+            best = Some(key);
+        }
+        for block in blocks.iter_mut() {
+            *block = IVec2::new(-block.y, block.x);
+        }
+    }
+    best.unwrap_or_default()
+}
+
+impl FromWorld for BlockPalette {
+    fn from_world(world: &mut World) -> Self {
+        let store = world.resource::<PkvStore>();
+        if let Ok(old) = store.get(DataKeys::BlockPalette) {
+            old
+        } else {
+            BlockPalette::preset(PalettePreset::Standard)
+        }
+    }
+}
+
+fn save_block_palette(mut store: ResMut<PkvStore>, palette: Res<BlockPalette>) {
+    if let Err(e) = store.set(DataKeys::BlockPalette, &*palette) {
+        error!("Failed to save block palette: {e:?}");
+    };
+}
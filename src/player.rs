@@ -0,0 +1,216 @@
+use bevy::{prelude::*, render::camera::Viewport, window::PrimaryWindow};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    ai::{AiDifficulty, AiPlayer},
+    board::Board,
+    deck::{CurrentDeck, Deck, PlayerInputs, TickInputs},
+    prelude::*,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PlayerSetup>()
+        .add_systems(
+            OnEnter(GameState::Playing),
+            spawn_players.after(crate::replay::start_game),
+        )
+        .add_systems(Update, split_viewports.run_if(in_state(GameState::Playing)))
+        .add_systems(OnExit(GameState::Playing), reset_viewport);
+}
+
+/// how far apart boards are in the world, far enough that one camera never sees two
+const BOARD_SPACING: f32 = 2000.;
+
+/// the entity holding a players board, deck, score and inputs
+/// blocks on the board are its children
+#[derive(Component)]
+#[require(Score, TickInputs)]
+pub struct Player {
+    pub index: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerKind {
+    Human,
+    Ai(AiDifficulty),
+    /// someone playing from another machine, their inputs arrive over the network
+    Remote,
+}
+
+/// who plays the next game, each player gets their own board of this size
+#[derive(Resource, Clone)]
+pub struct PlayerSetup {
+    pub players: Vec<PlayerKind>,
+    pub width: i32,
+    pub hight: i32,
+    /// score each player starts with, runs carry it over between stages
+    pub score: i32,
+}
+
+impl Default for PlayerSetup {
+    fn default() -> Self {
+        PlayerSetup::single(PlayerKind::Human)
+    }
+}
+
+impl PlayerSetup {
+    pub fn single(kind: PlayerKind) -> Self {
+        PlayerSetup {
+            players: vec![kind],
+            width: 10,
+            hight: 20,
+            score: 0,
+        }
+    }
+
+    pub fn versus(one: PlayerKind, two: PlayerKind) -> Self {
+        PlayerSetup {
+            players: vec![one, two],
+            ..PlayerSetup::single(PlayerKind::Human)
+        }
+    }
+
+    pub fn is_versus(&self) -> bool {
+        self.players.len() > 1
+    }
+
+    /// players sharing this keyboard
+    pub fn humans(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|kind| **kind == PlayerKind::Human)
+            .count()
+    }
+}
+
+/// the camera looking at this players board, player one uses the main camera
+#[derive(Component)]
+pub struct PlayerCamera(pub Entity);
+
+pub(crate) fn spawn_players(
+    mut commands: Commands,
+    setup: Res<PlayerSetup>,
+    deck: Res<Deck>,
+    mut rng: ResMut<GameRng>,
+    input_map: Res<InputMap<PlayerInputs>>,
+    asset_server: Res<AssetServer>,
+    main_camera: Query<&Transform, With<IsDefaultUiCamera>>,
+) {
+    let block_image = asset_server.load("block.png");
+    let camera = main_camera.iter().next().copied().unwrap_or_default();
+    for (index, kind) in setup.players.iter().enumerate() {
+        let offset = Vec3::X * BOARD_SPACING * index as f32;
+        // sharing the keyboard means nobody can have both sets of keys
+        let map = match kind {
+            PlayerKind::Human if setup.humans() > 1 => PlayerInputs::versus_map(index),
+            PlayerKind::Human => input_map.clone(),
+            PlayerKind::Ai(_) | PlayerKind::Remote => InputMap::default(),
+        };
+        let player = commands
+            .spawn((
+                Name::new(format!("Player {}", index + 1)),
+                Player { index },
+                Board::new(setup.width, setup.hight),
+                CurrentDeck::new(&deck, &mut **rng),
+                Score(setup.score),
+                map,
+                ActionState::<PlayerInputs>::default(),
+                Transform::from_translation(offset),
+                Visibility::Visible,
+                StateScoped(GameState::Playing),
+            ))
+            .id();
+        if let PlayerKind::Ai(difficulty) = kind {
+            commands.entity(player).insert(AiPlayer::new(*difficulty));
+        }
+        make_board(&mut commands, player, &setup, &block_image);
+        if index > 0 {
+            let camera = commands
+                .spawn((
+                    Camera2d,
+                    Camera {
+                        order: index as isize,
+                        ..Default::default()
+                    },
+                    Transform::from_translation(camera.translation + offset),
+                    StateScoped(GameState::Playing),
+                ))
+                .id();
+            commands.entity(player).insert(PlayerCamera(camera));
+        }
+    }
+}
+
+/// the wall around a board
+fn make_board(
+    commands: &mut Commands,
+    player: Entity,
+    setup: &PlayerSetup,
+    block_image: &Handle<Image>,
+) {
+    let wall = |x: i32, y: i32| {
+        (
+            Sprite {
+                image: block_image.clone(),
+                ..Default::default()
+            },
+            Transform::from_translation(Vec3::new(x as f32 * 32., y as f32 * 32., 0.0)),
+        )
+    };
+    commands.entity(player).with_children(|commands| {
+        for x in -1..=setup.width {
+            commands.spawn(wall(x, -1));
+            commands.spawn(wall(x, setup.hight));
+        }
+        for y in 0..setup.hight {
+            commands.spawn(wall(-1, y));
+            commands.spawn(wall(setup.width, y));
+        }
+    });
+}
+
+/// give each player an equal slice of the window
+fn split_viewports(
+    window: Query<&Window, With<PrimaryWindow>>,
+    players: Query<(&Player, Option<&PlayerCamera>)>,
+    mut main_camera: Query<&mut Camera, With<IsDefaultUiCamera>>,
+    mut cameras: Query<&mut Camera, Without<IsDefaultUiCamera>>,
+) {
+    let count = players.iter().count() as u32;
+    if count < 2 {
+        return;
+    }
+    let Ok(window) = window.single() else {
+        return;
+    };
+    let size = window.physical_size();
+    let slice = UVec2::new(size.x / count, size.y);
+    for (player, camera) in &players {
+        let camera = match camera {
+            Some(PlayerCamera(camera)) => cameras.get_mut(*camera).ok(),
+            None => main_camera.single_mut().ok(),
+        };
+        let Some(mut camera) = camera else {
+            continue;
+        };
+        let position = UVec2::new(slice.x * player.index as u32, 0);
+        if camera
+            .viewport
+            .as_ref()
+            .is_some_and(|v| v.physical_position == position && v.physical_size == slice)
+        {
+            continue;
+        }
+        camera.viewport = Some(Viewport {
+            physical_position: position,
+            physical_size: slice,
+            ..Default::default()
+        });
+    }
+}
+
+fn reset_viewport(mut main_camera: Query<&mut Camera, With<IsDefaultUiCamera>>) {
+    for mut camera in &mut main_camera {
+        camera.viewport = None;
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    blocks::{BlockImage, BlockSkin},
+    board::{Board, GameClock, OnBoard},
+    deck::{CurrentDeck, PlayerInputs, PlayerTarget},
+    mode::ModeProgress,
+    player::{Player, PlayerSetup},
+    prelude::*,
+    save::{BoardContents, BoardSnapshot, Cleared},
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_observer(snapshot_lock)
+        .add_systems(
+            OnEnter(GameState::Playing),
+            snapshot_start
+                .after(crate::player::spawn_players)
+                .run_if(resource_exists::<Practice>),
+        )
+        .add_systems(
+            Update,
+            undo_redo.run_if(resource_exists::<Practice>.and(in_state(GameState::Playing))),
+        )
+        .add_systems(OnExit(GameState::Playing), end_practice);
+}
+
+/// practice mode, every placed shape can be taken back and put down again
+#[derive(Resource, Default)]
+pub struct Practice {
+    /// the game as it was after each lock, the last one is where play carried on from
+    undo: Vec<Snapshot>,
+    /// snapshots that were undone, newest last
+    redo: Vec<Snapshot>,
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    board: BoardSnapshot,
+    current: CurrentDeck,
+    score: i32,
+    progress: ModeProgress,
+    rng: GameRng,
+    tick: u64,
+}
+
+pub fn start_practice(mut commands: Commands, mut state: ResMut<NextState<GameState>>) {
+    commands.remove_resource::<crate::run::Run>();
+    commands.remove_resource::<crate::dig::Dig>();
+    commands.insert_resource(Practice::default());
+    commands.insert_resource(crate::mode::GameMode::Endless);
+    commands.insert_resource(PlayerSetup::default());
+    state.set(GameState::Playing);
+}
+
+/// everything a snapshot is taken from besides the players own components
+#[derive(SystemParam)]
+struct SnapshotSource<'w, 's> {
+    contents: BoardContents<'w, 's>,
+    progress: Res<'w, ModeProgress>,
+    rng: Res<'w, GameRng>,
+    clock: Res<'w, GameClock>,
+}
+
+impl SnapshotSource<'_, '_> {
+    fn take(
+        &self,
+        player: Entity,
+        board: &Board,
+        current: &CurrentDeck,
+        score: &Score,
+    ) -> Snapshot {
+        let mut board = self.contents.capture(player, board);
+        board.lock_shapes();
+        Snapshot {
+            board,
+            current: current.clone(),
+            score: score.0,
+            progress: self.progress.clone(),
+            rng: self.rng.clone(),
+            tick: self.clock.tick,
+        }
+    }
+}
+
+/// the empty board, so the first shape can be undone too
+pub(crate) fn snapshot_start(
+    mut practice: ResMut<Practice>,
+    players: Query<(Entity, &Board, &CurrentDeck, &Score), With<Player>>,
+    source: SnapshotSource,
+) {
+    let Ok((player, board, current, score)) = players.single() else {
+        return;
+    };
+    practice.undo = vec![source.take(player, board, current, score)];
+    practice.redo.clear();
+}
+
+fn snapshot_lock(
+    trigger: Trigger<OnRemove, PlayerTarget>,
+    practice: Option<ResMut<Practice>>,
+    shapes: Query<(&OnBoard, Has<Cleared>)>,
+    players: Query<(&Board, &CurrentDeck, &Score), With<Player>>,
+    source: SnapshotSource,
+) {
+    let Some(mut practice) = practice else {
+        return;
+    };
+    let Ok((OnBoard(player), false)) = shapes.get(trigger.target()) else {
+        return;
+    };
+    let Ok((board, current, score)) = players.get(*player) else {
+        return;
+    };
+    let snapshot = source.take(*player, board, current, score);
+    // the pieces of a split shape lock together, they make one placement
+    if practice.undo.len() > 1 && practice.undo.last().map(|last| last.tick) == Some(snapshot.tick)
+    {
+        practice.undo.pop();
+    }
+    practice.undo.push(snapshot);
+    practice.redo.clear();
+}
+
+fn undo_redo(
+    mut commands: Commands,
+    mut practice: ResMut<Practice>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<PlayerInputs>,
+            &mut Board,
+            &mut CurrentDeck,
+            &mut Score,
+        ),
+        With<Player>,
+    >,
+    contents: BoardContents,
+    mut game: (ResMut<ModeProgress>, ResMut<GameRng>),
+    block_image: Res<BlockImage>,
+    skin: Res<BlockSkin>,
+) {
+    let Ok((player, inputs, mut board, mut current, mut score)) = players.single_mut() else {
+        return;
+    };
+    let snapshot = if inputs.just_pressed(&PlayerInputs::Undo) {
+        if practice.undo.len() < 2 {
+            return;
+        }
+        let undone = practice.undo.pop().unwrap();
+        practice.redo.push(undone);
+        practice.undo.last().unwrap().clone()
+    } else if inputs.just_pressed(&PlayerInputs::Redo) {
+        let Some(redone) = practice.redo.pop() else {
+            return;
+        };
+        practice.undo.push(redone.clone());
+        redone
+    } else {
+        return;
+    };
+
+    // the shape being placed goes too, it is dealt again from the restored deck
+    contents.clear(&mut commands, player, &mut board);
+    snapshot
+        .board
+        .rebuild(&mut commands, player, &mut board, &block_image, *skin);
+    *current = snapshot.current;
+    score.0 = snapshot.score;
+    let (progress, rng) = &mut game;
+    **progress = snapshot.progress;
+    **rng = snapshot.rng;
+}
+
+fn end_practice(mut commands: Commands) {
+    commands.remove_resource::<Practice>();
+}
@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+
+use crate::{
+    blocks::{BlockImage, BlockSkin, Power},
+    board::{on_step, spawn_static_block, BlockState, Board, LinesCleared, OnBoard, Shape},
+    deck::{CurrentDeck, PlayerTarget},
+    player::{Player, PlayerKind, PlayerSetup},
+    prelude::*,
+    save::Cleared,
+    ui::menus::Menu,
+};
+
+mod asset;
+
+pub fn plugin(app: &mut App) {
+    app.add_plugins(asset::plugin)
+        .init_resource::<PuzzleRecords>()
+        .add_observer(lock_puzzle_shape)
+        .add_systems(
+            OnEnter(GameState::Playing),
+            setup_puzzle
+                .after(crate::player::spawn_players)
+                .run_if(resource_exists::<ActivePuzzle>),
+        )
+        .add_systems(
+            FixedLast,
+            check_puzzle.run_if(
+                resource_exists::<ActivePuzzle>.and(in_state(GameState::Playing).and(on_step)),
+            ),
+        )
+        .add_systems(OnExit(GameState::Playing), end_puzzle)
+        .add_systems(
+            Update,
+            save_puzzle_records.run_if(resource_changed::<PuzzleRecords>),
+        );
+}
+
+/// what has to happen for a puzzle to be solved
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize)]
+pub enum PuzzleGoal {
+    /// clear this many lines in total
+    ClearLines(i32),
+    /// clear lines so that nothing is left on the board
+    PerfectClear,
+    /// clear three lines at once by spinning a T into place
+    TSpinTriple,
+}
+
+impl PuzzleGoal {
+    pub fn describe(self) -> String {
+        match self {
+            PuzzleGoal::ClearLines(lines) => format!("Clear {lines} lines"),
+            PuzzleGoal::PerfectClear => "Perfect clear".into(),
+            PuzzleGoal::TSpinTriple => "T-spin triple".into(),
+        }
+    }
+}
+
+/// a block on the board when a puzzle starts, it is not part of any shape
+#[derive(Clone, Debug)]
+pub struct PuzzleBlock {
+    pub cell: IVec2,
+    pub color: Color,
+    pub power: Option<Power>,
+}
+
+/// a board to start from, the shapes to solve it with and what counts as solving it
+#[derive(Asset, TypePath, Clone)]
+pub struct Puzzle {
+    pub name: String,
+    pub goal: PuzzleGoal,
+    pub width: i32,
+    pub hight: i32,
+    pub blocks: Vec<PuzzleBlock>,
+    /// dealt in this order, the puzzle is failed once they are all used
+    pub pieces: Vec<Shape>,
+}
+
+/// the puzzle being played and how far through it the player is
+#[derive(Resource)]
+pub struct ActivePuzzle {
+    puzzle: Puzzle,
+    lines: i32,
+    /// the last shape to lock was a T spun into place
+    t_spin: bool,
+    /// the board was replaced by a pasted one, solving that does not count
+    pasted: bool,
+}
+
+impl ActivePuzzle {
+    /// the board and pieces were swapped for ones the puzzle did not come with
+    pub fn paste(&mut self) {
+        self.pasted = true;
+        self.lines = 0;
+        self.t_spin = false;
+    }
+}
+
+/// names of every puzzle that has been solved
+#[derive(Resource, Default, serde::Serialize, serde::Deserialize)]
+pub struct PuzzleRecords {
+    completed: Vec<String>,
+}
+
+impl PuzzleRecords {
+    pub fn completed(&self, name: &str) -> bool {
+        self.completed.iter().any(|completed| completed == name)
+    }
+
+    fn complete(&mut self, name: &str) {
+        if !self.completed(name) {
+            self.completed.push(name.to_string());
+        }
+    }
+}
+
+impl FromWorld for PuzzleRecords {
+    fn from_world(world: &mut World) -> Self {
+        let store = world.resource::<PkvStore>();
+        if let Ok(records) = store.get(DataKeys::PuzzleRecords) {
+            records
+        } else {
+            PuzzleRecords::default()
+        }
+    }
+}
+
+fn save_puzzle_records(mut store: ResMut<PkvStore>, records: Res<PuzzleRecords>) {
+    if let Err(e) = store.set(DataKeys::PuzzleRecords, &*records) {
+        error!("Failed to save puzzle records: {e:?}");
+    };
+}
+
+/// play `puzzle` on a board of its own size
+pub fn start_puzzle(puzzle: Puzzle) -> impl FnMut(Commands, ResMut<NextState<GameState>>) {
+    move |mut commands, mut state| {
+        commands.remove_resource::<crate::run::Run>();
+        commands.remove_resource::<crate::dig::Dig>();
+        commands.insert_resource(crate::mode::GameMode::Endless);
+        commands.insert_resource(PlayerSetup {
+            width: puzzle.width,
+            hight: puzzle.hight,
+            ..PlayerSetup::single(PlayerKind::Human)
+        });
+        commands.insert_resource(ActivePuzzle {
+            puzzle: puzzle.clone(),
+            lines: 0,
+            t_spin: false,
+            pasted: false,
+        });
+        state.set(GameState::Playing);
+    }
+}
+
+fn setup_puzzle(
+    mut commands: Commands,
+    puzzle: Res<ActivePuzzle>,
+    mut players: Query<(Entity, &mut Board, &mut CurrentDeck), With<Player>>,
+    block_image: Res<BlockImage>,
+    skin: Res<BlockSkin>,
+) {
+    info!("{}: {}", puzzle.puzzle.name, puzzle.puzzle.goal.describe());
+    for (entity, mut board, mut deck) in &mut players {
+        for block in puzzle.puzzle.blocks.iter() {
+            spawn_static_block(
+                &mut commands,
+                entity,
+                &mut board,
+                block.cell,
+                block_image.sprite(*skin, block.color),
+                block.power,
+            );
+        }
+        *deck = CurrentDeck::fixed(puzzle.puzzle.pieces.clone());
+    }
+}
+
+/// a T is the only shape of four blocks where one block touches all the others
+fn is_t(shape: &Shape) -> bool {
+    shape.blocks.len() == 4
+        && shape.blocks.iter().any(|block| {
+            [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                .into_iter()
+                .filter(|side| shape.blocks.contains(&(block + side)))
+                .count()
+                == 3
+        })
+}
+
+/// shapes cleared off the board or emptied by a power were never placed, so they leave `t_spin` alone
+fn lock_puzzle_shape(
+    trigger: Trigger<OnRemove, PlayerTarget>,
+    shapes: Query<(&Shape, &PlayerTarget, &OnBoard, Has<Cleared>)>,
+    boards: Query<&Board>,
+    puzzle: Option<ResMut<ActivePuzzle>>,
+) {
+    let Some(mut puzzle) = puzzle else {
+        return;
+    };
+    let Ok((shape, target, on_board, false)) = shapes.get(trigger.target()) else {
+        return;
+    };
+    if shape.blocks.is_empty() {
+        return;
+    }
+    let Ok(board) = boards.get(on_board.0) else {
+        return;
+    };
+    puzzle.t_spin = is_t(shape) && target.spun(shape, board);
+}
+
+/// true if the only blocks left on `board` belong to the shape still being placed
+fn board_clear(board: &Board, active: Option<&Shape>) -> bool {
+    (0..board.hight())
+        .flat_map(|y| (0..board.width()).map(move |x| IVec2::new(x, y)))
+        .filter(|cell| matches!(board.get(*cell), BlockState::Contains(_)))
+        .all(|cell| {
+            active.is_some_and(|shape| {
+                shape
+                    .blocks
+                    .iter()
+                    .any(|block| shape.center + block == cell)
+            })
+        })
+}
+
+fn check_puzzle(
+    mut puzzle: ResMut<ActivePuzzle>,
+    mut records: ResMut<PuzzleRecords>,
+    mut events: EventReader<LinesCleared>,
+    players: Query<(&Board, &CurrentDeck), With<Player>>,
+    active: Query<&Shape, With<PlayerTarget>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<Menu>>,
+) {
+    let Ok((board, deck)) = players.single() else {
+        return;
+    };
+    let active = active.iter().next();
+    let mut solved = false;
+    for LinesCleared { lines, .. } in events.read() {
+        puzzle.lines += lines;
+        solved |= match puzzle.puzzle.goal {
+            PuzzleGoal::ClearLines(goal) => puzzle.lines >= goal,
+            PuzzleGoal::PerfectClear => board_clear(board, active),
+            PuzzleGoal::TSpinTriple => puzzle.t_spin && *lines == 3,
+        };
+    }
+    if solved && puzzle.pasted {
+        info!("Pasted board solved, {} stays unsolved", puzzle.puzzle.name);
+    } else if solved {
+        info!("Puzzle {} solved", puzzle.puzzle.name);
+        records.complete(&puzzle.puzzle.name);
+    } else if deck.is_empty() && active.is_none() && !board.has_moved() {
+        info!("Puzzle {} failed, out of pieces", puzzle.puzzle.name);
+    } else {
+        return;
+    }
+    state.set(GameState::InMenu);
+    menu.set(Menu::Puzzles);
+}
+
+fn end_puzzle(mut commands: Commands) {
+    commands.remove_resource::<ActivePuzzle>();
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadedFolder},
+    prelude::*,
+};
+
+use super::{Puzzle, PuzzleBlock, PuzzleGoal};
+use crate::{
+    blocks::Power,
+    board::Board,
+    deck::{Deck, DeckProblem, ShapeFile},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Puzzle>()
+        .init_asset_loader::<PuzzleLoader>()
+        .init_resource::<PuzzleLibrary>();
+}
+
+#[derive(serde::Deserialize)]
+struct PuzzleFile {
+    name: String,
+    goal: PuzzleGoal,
+    #[serde(default = "default_width")]
+    width: i32,
+    #[serde(default = "default_hight")]
+    hight: i32,
+    /// the board as it looks, top row first and the last row on the floor, `.` is an empty cell
+    rows: Vec<String>,
+    /// what each letter in `rows` stands for
+    blocks: HashMap<char, BlockFile>,
+    /// the shapes dealt in order, written like the shapes in a deck
+    pieces: Vec<ShapeFile>,
+}
+
+fn default_width() -> i32 {
+    10
+}
+
+fn default_hight() -> i32 {
+    20
+}
+
+#[derive(serde::Deserialize)]
+struct BlockFile {
+    /// hex colour like "#808080"
+    color: String,
+    /// the name of the power the block has
+    #[serde(default)]
+    power: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum PuzzleLoadError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    /// the file was read but does not make a puzzle that can be played
+    Invalid(String),
+}
+
+impl std::fmt::Display for PuzzleLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PuzzleLoadError::Io(e) => write!(f, "failed to read puzzle: {e}"),
+            PuzzleLoadError::Ron(e) => write!(f, "failed to parse puzzle: {e}"),
+            PuzzleLoadError::Invalid(e) => write!(f, "invalid puzzle: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleLoadError {}
+
+impl From<std::io::Error> for PuzzleLoadError {
+    fn from(value: std::io::Error) -> Self {
+        PuzzleLoadError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for PuzzleLoadError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        PuzzleLoadError::Ron(value)
+    }
+}
+
+impl From<DeckProblem> for PuzzleLoadError {
+    fn from(value: DeckProblem) -> Self {
+        PuzzleLoadError::Invalid(format!("piece problem, {value}"))
+    }
+}
+
+#[derive(Default)]
+struct PuzzleLoader;
+
+impl AssetLoader for PuzzleLoader {
+    type Asset = Puzzle;
+    type Settings = ();
+    type Error = PuzzleLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Puzzle, PuzzleLoadError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = ron::de::from_bytes::<PuzzleFile>(&bytes)?;
+        if file.rows.len() as i32 > file.hight {
+            return Err(PuzzleLoadError::Invalid(format!(
+                "{} rows do not fit on a board {} high",
+                file.rows.len(),
+                file.hight
+            )));
+        }
+        let mut blocks = Vec::new();
+        for (row, line) in file.rows.iter().rev().enumerate() {
+            if line.chars().count() as i32 > file.width {
+                return Err(PuzzleLoadError::Invalid(format!(
+                    "row {line:?} does not fit on a board {} wide",
+                    file.width
+                )));
+            }
+            for (x, symbol) in line.chars().enumerate() {
+                if symbol == '.' {
+                    continue;
+                }
+                let Some(block) = file.blocks.get(&symbol) else {
+                    return Err(PuzzleLoadError::Invalid(format!(
+                        "{symbol:?} is not in the blocks list"
+                    )));
+                };
+                let color = Srgba::hex(&block.color).map_err(|_| {
+                    PuzzleLoadError::Invalid(format!("invalid colour {}", block.color))
+                })?;
+                let power = match &block.power {
+                    Some(name) => Some(Power::from_name(name).ok_or_else(|| {
+                        PuzzleLoadError::Invalid(format!("unknown power {name}"))
+                    })?),
+                    None => None,
+                };
+                blocks.push(PuzzleBlock {
+                    cell: IVec2::new(x as i32, row as i32),
+                    color: color.into(),
+                    power,
+                });
+            }
+        }
+        let mut problems = Vec::new();
+        let pieces = file
+            .pieces
+            .into_iter()
+            .enumerate()
+            .map(|(index, piece)| piece.into_shape(index, &mut problems))
+            .collect::<Vec<_>>();
+        if pieces.is_empty() {
+            return Err(PuzzleLoadError::Invalid("there are no pieces".into()));
+        }
+        // the same piece coming up more than once is normal for a puzzle
+        problems.extend(
+            Deck::from_shapes(pieces.clone())
+                .validate(&Board::new(file.width, file.hight))
+                .into_iter()
+                .filter(|problem| !matches!(problem, DeckProblem::DuplicateShape { .. })),
+        );
+        if let Some(problem) = problems.into_iter().next() {
+            return Err(problem.into());
+        }
+        Ok(Puzzle {
+            name: file.name,
+            goal: file.goal,
+            width: file.width,
+            hight: file.hight,
+            blocks,
+            pieces,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle.ron"]
+    }
+}
+
+/// every puzzle in the `puzzles` folder
+#[derive(Resource)]
+pub struct PuzzleLibrary(pub Handle<LoadedFolder>);
+
+impl FromWorld for PuzzleLibrary {
+    fn from_world(world: &mut World) -> Self {
+        PuzzleLibrary(world.resource::<AssetServer>().load_folder("puzzles"))
+    }
+}
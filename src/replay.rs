@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+
+use crate::{
+    board::GameClock,
+    deck::{Deck, ReadInputs, TickInputs},
+    garbage::AddGarbage,
+    mode::{GameMode, OtherModes},
+    player::{Player, PlayerKind, PlayerSetup},
+    prelude::*,
+    ui::menus::Menu,
+};
+
+mod file;
+
+pub use file::{
+    delete_replay, list_replays, load_header, load_replay, rename_replay, save_replay, ReplayError,
+    ReplayHeader,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Playing), start_game)
+        .add_systems(OnExit(GameState::Playing), finish_game)
+        .add_systems(
+            FixedFirst,
+            (
+                record_inputs.run_if(resource_exists::<Recording>),
+                play_inputs.run_if(resource_exists::<Playback>),
+            )
+                .after(ReadInputs)
+                .after(crate::board::advance_clock)
+                .run_if(in_state(GameState::Playing)),
+        );
+}
+
+/// everything needed to play a game again exactly as it happened
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub width: i32,
+    pub hight: i32,
+    pub deck: Deck,
+    pub mode: GameMode,
+    /// score at the start, runs carry it over from earlier stages
+    pub score: i32,
+    /// the tick each change of input happened on, inputs stay the same until the next entry
+    pub inputs: Vec<(u64, TickInputs)>,
+    /// rows of garbage and the tick they were added on
+    pub garbage: Vec<(u64, i32)>,
+    /// how long the game lasted
+    pub ticks: u64,
+}
+
+impl Replay {
+    pub fn inputs_at(&self, tick: u64) -> TickInputs {
+        let index = self.inputs.partition_point(|(at, _)| *at <= tick);
+        if index == 0 {
+            return TickInputs::default();
+        }
+        self.inputs[index - 1].1
+    }
+}
+
+/// the game being played right now
+#[derive(Resource)]
+pub struct Recording {
+    pub replay: Replay,
+    /// score so far, saved in the header when the game ends
+    pub score: i32,
+}
+
+/// while this exists the game is driven by the replay instead of the player
+#[derive(Resource)]
+pub struct Playback(pub Replay);
+
+/// start playing `replay` back the next time the game starts
+pub fn watch(commands: &mut Commands, replay: Replay) {
+    commands.remove_resource::<crate::run::Run>();
+    commands.remove_resource::<crate::dig::Dig>();
+    commands.insert_resource(Playback(replay));
+}
+
+/// the player replays are recorded from and played back to
+fn first_player<'a, T>(players: impl Iterator<Item = (&'a Player, T)>) -> Option<T> {
+    players
+        .filter(|(player, _)| player.index == 0)
+        .map(|(_, item)| item)
+        .next()
+}
+
+pub(crate) fn start_game(
+    mut commands: Commands,
+    mut setup: ResMut<PlayerSetup>,
+    mut deck: ResMut<Deck>,
+    mut mode: ResMut<GameMode>,
+    mut rng: ResMut<GameRng>,
+    other_modes: OtherModes,
+    resume: Option<Res<crate::save::Resume>>,
+) {
+    let seed = if let Some(Playback(replay)) = other_modes.playback.as_deref() {
+        *setup = PlayerSetup {
+            width: replay.width,
+            hight: replay.hight,
+            score: replay.score,
+            ..PlayerSetup::single(PlayerKind::Human)
+        };
+        *deck = replay.deck.clone();
+        *mode = replay.mode;
+        replay.seed
+    } else {
+        let seed = rand::random();
+        // only single player games from an empty board that are never undone can be replayed
+        if !setup.is_versus()
+            && other_modes.puzzle.is_none()
+            && other_modes.practice.is_none()
+            && resume.is_none()
+        {
+            commands.insert_resource(Recording {
+                replay: Replay {
+                    seed,
+                    width: setup.width,
+                    hight: setup.hight,
+                    deck: deck.clone(),
+                    mode: *mode,
+                    score: setup.score,
+                    inputs: Vec::new(),
+                    garbage: Vec::new(),
+                    ticks: 0,
+                },
+                score: setup.score,
+            });
+        }
+        seed
+    };
+    *rng = GameRng::seeded(seed);
+}
+
+fn record_inputs(
+    clock: Res<GameClock>,
+    players: Query<(&Player, (&TickInputs, &Score))>,
+    mut garbage: EventReader<AddGarbage>,
+    mut recording: ResMut<Recording>,
+) {
+    let Some((inputs, score)) = first_player(players.iter()) else {
+        return;
+    };
+    recording.score = score.0;
+    let replay = &mut recording.replay;
+    if replay.inputs_at(clock.tick) != *inputs {
+        replay.inputs.push((clock.tick, *inputs));
+    }
+    for AddGarbage { rows, .. } in garbage.read() {
+        replay.garbage.push((clock.tick, *rows));
+    }
+    replay.ticks = clock.tick;
+}
+
+fn play_inputs(
+    clock: Res<GameClock>,
+    playback: Res<Playback>,
+    mut players: Query<(&Player, (Entity, &mut TickInputs))>,
+    mut garbage: EventWriter<AddGarbage>,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<Menu>>,
+) {
+    let replay = &playback.0;
+    if clock.tick > replay.ticks {
+        info!("Replay finished");
+        state.set(GameState::InMenu);
+        menu.set(Menu::Main);
+        return;
+    }
+    let Some((board, mut inputs)) = first_player(players.iter_mut()) else {
+        return;
+    };
+    *inputs = replay.inputs_at(clock.tick);
+    for (_, rows) in replay.garbage.iter().filter(|(at, _)| *at == clock.tick) {
+        garbage.write(AddGarbage { board, rows: *rows });
+    }
+}
+
+fn finish_game(
+    mut commands: Commands,
+    recording: Option<Res<Recording>>,
+    playback: Option<Res<Playback>>,
+    selected: Res<crate::deck::SelectedDeck>,
+    run: Option<Res<crate::run::Run>>,
+) {
+    if let Some(recording) = recording {
+        let replay = recording.replay.clone();
+        let deck = if run.is_some() {
+            "Run".to_string()
+        } else {
+            selected.0.clone()
+        };
+        let header = ReplayHeader::new(&replay, deck, recording.score);
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        let name = format!("{} {time}", replay.mode.name());
+        if let Err(e) = save_replay(&name, header, replay) {
+            error!("Failed to save replay: {e}");
+        }
+        commands.remove_resource::<Recording>();
+    }
+    if playback.is_some() {
+        commands.remove_resource::<Playback>();
+    }
+}
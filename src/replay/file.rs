@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use super::Replay;
+use crate::mode::GameMode;
+
+/// bumped whenever `Replay` changes in a way old files can not be read,
+/// or the game plays the same inputs out differently so old replays would desync
+/// 2: held inputs repeat per player and garbage rows are static
+/// 3: shapes kick when they do not fit where they turn
+pub const REPLAY_FORMAT: u32 = 3;
+/// replays are kept next to the game rather than in assets so they can be written
+pub const REPLAY_DIR: &str = "replays";
+const EXTENSION: &str = ".replay.ron";
+
+/// what the replay browser shows without reading the whole replay
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReplayHeader {
+    pub format: u32,
+    pub game_version: String,
+    pub mode: GameMode,
+    pub seed: u64,
+    pub deck: String,
+    pub score: i32,
+}
+
+impl ReplayHeader {
+    pub fn new(replay: &Replay, deck: String, score: i32) -> Self {
+        ReplayHeader {
+            format: REPLAY_FORMAT,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            mode: replay.mode,
+            seed: replay.seed,
+            deck,
+            score,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ReplayFile {
+    header: ReplayHeader,
+    replay: Replay,
+}
+
+/// only the parts of the header every format has, read first so old files give a useful error
+#[derive(serde::Deserialize)]
+struct VersionCheck {
+    header: FormatHeader,
+}
+
+#[derive(serde::Deserialize)]
+struct FormatHeader {
+    format: u32,
+    game_version: String,
+}
+
+#[derive(serde::Deserialize)]
+struct HeaderOnly {
+    header: ReplayHeader,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Write(ron::Error),
+    Incompatible { format: u32, game_version: String },
+    InvalidName(String),
+    Exists(String),
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "failed to read replay: {e}"),
+            ReplayError::Ron(e) => write!(f, "failed to parse replay: {e}"),
+            ReplayError::Write(e) => write!(f, "failed to write replay: {e}"),
+            ReplayError::Incompatible {
+                format,
+                game_version,
+            } => write!(
+                f,
+                "replay is from version {game_version} using format {format}, this version can only play format {REPLAY_FORMAT}"
+            ),
+            ReplayError::InvalidName(name) => write!(f, "{name:?} is not a valid replay name"),
+            ReplayError::Exists(name) => write!(f, "a replay called {name:?} already exists"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(value: std::io::Error) -> Self {
+        ReplayError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for ReplayError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        ReplayError::Ron(value)
+    }
+}
+
+impl From<ron::Error> for ReplayError {
+    fn from(value: ron::Error) -> Self {
+        ReplayError::Write(value)
+    }
+}
+
+fn replay_path(name: &str) -> Result<PathBuf, ReplayError> {
+    // the name ends up as a file name so it can't leave the replay folder
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(ReplayError::InvalidName(name.to_string()));
+    }
+    Ok(PathBuf::from(REPLAY_DIR).join(format!("{name}{EXTENSION}")))
+}
+
+fn check_version(text: &str) -> Result<(), ReplayError> {
+    let check = ron::from_str::<VersionCheck>(text)?;
+    if check.header.format != REPLAY_FORMAT {
+        return Err(ReplayError::Incompatible {
+            format: check.header.format,
+            game_version: check.header.game_version,
+        });
+    }
+    Ok(())
+}
+
+pub fn save_replay(name: &str, header: ReplayHeader, replay: Replay) -> Result<(), ReplayError> {
+    let path = replay_path(name)?;
+    std::fs::create_dir_all(REPLAY_DIR)?;
+    let text = ron::to_string(&ReplayFile { header, replay })?;
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+pub fn load_replay(name: &str) -> Result<(ReplayHeader, Replay), ReplayError> {
+    let text = std::fs::read_to_string(replay_path(name)?)?;
+    check_version(&text)?;
+    let file = ron::from_str::<ReplayFile>(&text)?;
+    Ok((file.header, file.replay))
+}
+
+pub fn load_header(name: &str) -> Result<ReplayHeader, ReplayError> {
+    let text = std::fs::read_to_string(replay_path(name)?)?;
+    check_version(&text)?;
+    Ok(ron::from_str::<HeaderOnly>(&text)?.header)
+}
+
+/// names of every replay in the replay folder, sorted
+pub fn list_replays() -> Result<Vec<String>, ReplayError> {
+    let dir = match std::fs::read_dir(REPLAY_DIR) {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut names = Vec::new();
+    for entry in dir {
+        let file_name = entry?.file_name();
+        let Some(name) = file_name.to_str().and_then(|n| n.strip_suffix(EXTENSION)) else {
+            continue;
+        };
+        names.push(name.to_string());
+    }
+    names.sort();
+    Ok(names)
+}
+
+pub fn rename_replay(from: &str, to: &str) -> Result<(), ReplayError> {
+    let path = replay_path(to)?;
+    if path.exists() {
+        return Err(ReplayError::Exists(to.to_string()));
+    }
+    std::fs::rename(replay_path(from)?, path)?;
+    Ok(())
+}
+
+pub fn delete_replay(name: &str) -> Result<(), ReplayError> {
+    std::fs::remove_file(replay_path(name)?)?;
+    Ok(())
+}
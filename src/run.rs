@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use rand::{seq::IndexedRandom, Rng};
+
+use crate::{
+    blocks::Power,
+    board::{LinesCleared, Shape},
+    deck::Deck,
+    player::{Player, PlayerSetup},
+    prelude::*,
+    ui::menus::Menu,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedLast,
+        count_lines.run_if(resource_exists::<Run>.and(in_state(GameState::Playing))),
+    );
+}
+
+/// a roguelike run made of stages, the deck is changed between each stage and kept for the rest of the run
+#[derive(Resource, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Run {
+    pub stage: u32,
+    pub lines: i32,
+}
+
+impl Run {
+    /// lines that need to be cleared to finish the current stage
+    pub fn target(&self) -> i32 {
+        5 + self.stage as i32 * 3
+    }
+
+    pub fn next_stage(&mut self) {
+        self.stage += 1;
+        self.lines = 0;
+    }
+}
+
+pub fn start_run(
+    mut commands: Commands,
+    mut deck: ResMut<Deck>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    *deck = Deck::classic();
+    commands.insert_resource(PlayerSetup::default());
+    commands.insert_resource(Run::default());
+    commands.insert_resource(crate::mode::GameMode::Endless);
+    state.set(GameState::Playing);
+}
+
+fn count_lines(
+    mut events: EventReader<LinesCleared>,
+    mut run: ResMut<Run>,
+    mut setup: ResMut<PlayerSetup>,
+    scores: Query<&Score, With<Player>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<Menu>>,
+) {
+    for LinesCleared { lines, .. } in events.read() {
+        run.lines += lines;
+    }
+    if run.lines >= run.target() {
+        // the board goes away with the stage but the score stays for the next one
+        setup.score = scores.iter().map(|score| score.0).sum();
+        state.set(GameState::InMenu);
+        menu.set(Menu::Draft);
+    }
+}
+
+/// a change to the deck that can be picked between stages
+#[derive(Clone)]
+pub enum DraftChoice {
+    AddShape(Shape),
+    RemoveShape(usize),
+    AttachPower(usize, Power),
+    /// spread the shapes first power to another one of its blocks
+    Upgrade(usize),
+}
+
+impl DraftChoice {
+    pub fn apply(&self, deck: &mut Deck) {
+        match self {
+            DraftChoice::AddShape(shape) => deck.add(shape.clone()),
+            DraftChoice::RemoveShape(index) => {
+                deck.remove(*index);
+            }
+            DraftChoice::AttachPower(index, power) => {
+                let Some(shape) = deck.shape_mut(*index) else {
+                    return;
+                };
+                let block = free_block(shape).unwrap_or(0);
+                shape.powers.retain(|(i, _)| *i != block);
+                shape.powers.push((block, *power));
+            }
+            DraftChoice::Upgrade(index) => {
+                let Some(shape) = deck.shape_mut(*index) else {
+                    return;
+                };
+                let (Some(&(_, power)), Some(block)) = (shape.powers.first(), free_block(shape))
+                else {
+                    return;
+                };
+                shape.powers.push((block, power));
+            }
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            DraftChoice::AddShape(_) => "Add".into(),
+            DraftChoice::RemoveShape(_) => "Remove".into(),
+            DraftChoice::AttachPower(_, power) => power.name().into(),
+            DraftChoice::Upgrade(_) => "Upgrade".into(),
+        }
+    }
+
+    /// the shape this choice changes as it would look after picking it
+    pub fn preview(&self, deck: &Deck) -> Option<Shape> {
+        match self {
+            DraftChoice::AddShape(shape) => Some(shape.clone()),
+            DraftChoice::RemoveShape(index) => deck.shapes().get(*index).cloned(),
+            DraftChoice::AttachPower(index, _) | DraftChoice::Upgrade(index) => {
+                let mut deck = Deck::from_shapes(deck.shapes().to_vec());
+                self.apply(&mut deck);
+                deck.shapes().get(*index).cloned()
+            }
+        }
+    }
+}
+
+fn free_block(shape: &Shape) -> Option<usize> {
+    (0..shape.blocks.len()).find(|block| !shape.powers.iter().any(|(i, _)| i == block))
+}
+
+/// pick a few random changes to offer the player
+pub fn draft_offers(deck: &Deck, rng: &mut impl Rng) -> Vec<DraftChoice> {
+    let pool = Deck::classic();
+    let mut offers = Vec::new();
+    if let Some(shape) = pool.shapes().choose(rng) {
+        offers.push(DraftChoice::AddShape(shape.clone()));
+    }
+    if deck.shapes().len() > 1 {
+        offers.push(DraftChoice::RemoveShape(
+            rng.random_range(0..deck.shapes().len()),
+        ));
+    }
+    if !deck.shapes().is_empty() {
+        let power = *Power::ALL.choose(rng).expect("At least one power");
+        offers.push(DraftChoice::AttachPower(
+            rng.random_range(0..deck.shapes().len()),
+            power,
+        ));
+    }
+    let upgradable = deck
+        .shapes()
+        .iter()
+        .enumerate()
+        .filter(|(_, shape)| !shape.powers.is_empty() && free_block(shape).is_some())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    if let Some(index) = upgradable.choose(rng) {
+        offers.push(DraftChoice::Upgrade(*index));
+    }
+    offers
+}
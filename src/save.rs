@@ -0,0 +1,331 @@
+use bevy::{
+    ecs::system::SystemParam,
+    platform_support::{collections::HashSet, hash::FixedHasher},
+    prelude::*,
+};
+use bevy_pkv::PkvStore;
+
+use crate::{
+    blocks::{Block, BlockImage, BlockSkin, Bomb, Lightning, Power},
+    board::{spawn_board_block, BlockState, Board, GameClock, OnBoard, Shape},
+    deck::{CurrentDeck, Deck, PlayerTarget},
+    mode::{GameMode, ModeProgress, OtherModes},
+    player::{Player, PlayerKind, PlayerSetup},
+    prelude::*,
+    run::Run,
+    ui::menus::Menu,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::Playing),
+        resume_game
+            .after(crate::player::spawn_players)
+            .after(crate::mode::start_mode)
+            .after(crate::board::reset_clock)
+            .run_if(resource_exists::<Resume>),
+    );
+}
+
+/// everything needed to carry on a single player game after the app is closed
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct SavedGame {
+    width: i32,
+    hight: i32,
+    board: BoardSnapshot,
+    current: CurrentDeck,
+    deck: Deck,
+    score: i32,
+    mode: GameMode,
+    progress: ModeProgress,
+    tick: u64,
+    step_ticks: u32,
+    /// the rng is reseeded from this when the game is saved so carrying on gives the same shapes
+    seed: u64,
+    run: Option<Run>,
+}
+
+/// the blocks and shapes on a board, enough to put it back exactly as it was
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct BoardSnapshot {
+    /// every filled cell on the board
+    blocks: Vec<SavedBlock>,
+    shapes: Vec<SavedShape>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct SavedBlock {
+    cell: IVec2,
+    /// index into `shapes`, `None` for static blocks
+    shape: Option<usize>,
+    color: Color,
+    power: Option<Power>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct SavedShape {
+    shape: Shape,
+    /// the shape the player is moving
+    active: bool,
+}
+
+/// the saved game being carried on, it is taken once the board has been rebuilt
+#[derive(Resource)]
+pub struct Resume(SavedGame);
+
+/// true if there is a game to continue
+pub fn has_saved_game(store: &PkvStore) -> bool {
+    matches!(
+        store.get::<Option<SavedGame>>(DataKeys::SavedGame),
+        Ok(Some(_))
+    )
+}
+
+/// what a save holds apart from the board, read when the game is quit
+#[derive(SystemParam)]
+pub struct SaveSource<'w> {
+    setup: Res<'w, PlayerSetup>,
+    deck: Res<'w, Deck>,
+    mode: Res<'w, GameMode>,
+    progress: Res<'w, ModeProgress>,
+    clock: Res<'w, GameClock>,
+    rng: ResMut<'w, GameRng>,
+}
+
+/// what a save puts back apart from the board and the player
+#[derive(SystemParam)]
+pub struct SaveTarget<'w> {
+    progress: ResMut<'w, ModeProgress>,
+    clock: ResMut<'w, GameClock>,
+    rng: ResMut<'w, GameRng>,
+}
+
+/// leave the game for the main menu, single player games are saved so they can be continued later
+pub fn quit_game(
+    mut store: ResMut<PkvStore>,
+    players: Query<(Entity, &Board, &CurrentDeck, &Score), With<Player>>,
+    contents: BoardContents,
+    mut source: SaveSource,
+    other_modes: OtherModes,
+    mut state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<NextState<Menu>>,
+) {
+    state.set(GameState::InMenu);
+    menu.set(Menu::Main);
+    // digs and puzzles are short and are set up in ways a save does not keep
+    if source.setup.players != [PlayerKind::Human]
+        || other_modes.dig.is_some()
+        || other_modes.puzzle.is_some()
+        || other_modes.playback.is_some()
+        || other_modes.practice.is_some()
+    {
+        return;
+    }
+    let Ok((player, board, current, score)) = players.single() else {
+        return;
+    };
+    let saved = SavedGame {
+        width: board.width(),
+        hight: board.hight(),
+        board: contents.capture(player, board),
+        current: current.clone(),
+        deck: source.deck.clone(),
+        score: score.0,
+        mode: *source.mode,
+        progress: source.progress.clone(),
+        tick: source.clock.tick,
+        step_ticks: source.clock.step_ticks,
+        seed: source.rng.reseed(),
+        run: other_modes.run.as_deref().cloned(),
+    };
+    if let Err(e) = store.set(DataKeys::SavedGame, &Some(saved)) {
+        error!("Failed to save game: {e:?}");
+    } else {
+        info!("Game saved");
+    }
+}
+
+/// carry on the saved game, there is only ever one so it is gone once it has been continued
+pub fn continue_game(
+    mut commands: Commands,
+    mut store: ResMut<PkvStore>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let saved = match store.get::<Option<SavedGame>>(DataKeys::SavedGame) {
+        Ok(Some(saved)) => saved,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to load saved game: {e:?}");
+            return;
+        }
+    };
+    if let Err(e) = store.set(DataKeys::SavedGame, &None::<SavedGame>) {
+        error!("Failed to clear saved game: {e:?}");
+    }
+    commands.remove_resource::<crate::dig::Dig>();
+    match saved.run.clone() {
+        Some(run) => commands.insert_resource(run),
+        None => commands.remove_resource::<Run>(),
+    }
+    commands.insert_resource(saved.deck.clone());
+    commands.insert_resource(saved.mode);
+    commands.insert_resource(PlayerSetup {
+        width: saved.width,
+        hight: saved.hight,
+        score: saved.score,
+        ..PlayerSetup::single(PlayerKind::Human)
+    });
+    commands.insert_resource(Resume(saved));
+    state.set(GameState::Playing);
+}
+
+/// put the saved board back, the shapes are spawned here so `spawn_shape` skips them
+fn resume_game(
+    mut commands: Commands,
+    resume: Res<Resume>,
+    mut players: Query<(Entity, &mut Board, &mut CurrentDeck, &mut Score), With<Player>>,
+    block_image: Res<BlockImage>,
+    skin: Res<BlockSkin>,
+    mut target: SaveTarget,
+) {
+    let Resume(saved) = &*resume;
+    commands.remove_resource::<Resume>();
+    let Ok((player, mut board, mut current, mut score)) = players.single_mut() else {
+        return;
+    };
+    *current = saved.current.clone();
+    score.0 = saved.score;
+    *target.progress = saved.progress.clone();
+    target.clock.tick = saved.tick;
+    target.clock.step_ticks = saved.step_ticks;
+    *target.rng = GameRng::seeded(saved.seed);
+    saved
+        .board
+        .rebuild(&mut commands, player, &mut board, &block_image, *skin);
+}
+
+/// put on shapes removed by `BoardContents::clear`, losing the player target this way is not a lock
+#[derive(Component)]
+pub struct Cleared;
+
+/// what is on each board, for taking a `BoardSnapshot`
+#[derive(SystemParam)]
+pub struct BoardContents<'w, 's> {
+    shapes: Query<'w, 's, (Entity, &'static Shape, &'static OnBoard, Has<PlayerTarget>)>,
+    blocks: Query<'w, 's, (&'static Block, &'static Sprite, Has<Lightning>, Has<Bomb>)>,
+}
+
+impl BoardContents<'_, '_> {
+    pub fn capture(&self, player: Entity, board: &Board) -> BoardSnapshot {
+        let shapes = self
+            .shapes
+            .iter()
+            .filter(|(_, _, on_board, _)| on_board.0 == player)
+            .collect::<Vec<_>>();
+        let mut blocks = Vec::new();
+        for y in 0..board.hight() {
+            for x in 0..board.width() {
+                let cell = IVec2::new(x, y);
+                let BlockState::Contains(entity) = board.get(cell) else {
+                    continue;
+                };
+                let Ok((block, sprite, lightning, bomb)) = self.blocks.get(entity) else {
+                    continue;
+                };
+                let shape = block
+                    .shape
+                    .and_then(|shape| shapes.iter().position(|(entity, ..)| *entity == shape));
+                let power = match (lightning, bomb) {
+                    (true, _) => Some(Power::Lightning),
+                    (_, true) => Some(Power::Bomb),
+                    _ => None,
+                };
+                blocks.push(SavedBlock {
+                    cell,
+                    shape,
+                    color: sprite.color,
+                    power,
+                });
+            }
+        }
+        BoardSnapshot {
+            blocks,
+            shapes: shapes
+                .iter()
+                .map(|(_, shape, _, active)| SavedShape {
+                    shape: (*shape).clone(),
+                    active: *active,
+                })
+                .collect(),
+        }
+    }
+
+    /// despawn every block and shape on the board of `player`, leaving it empty
+    pub fn clear(&self, commands: &mut Commands, player: Entity, board: &mut Board) {
+        for y in 0..board.hight() {
+            for x in 0..board.width() {
+                if let BlockState::Contains(entity) = board.get(IVec2::new(x, y)) {
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+        for (entity, _, on_board, _) in self.shapes.iter() {
+            if on_board.0 == player {
+                commands.entity(entity).insert(Cleared).despawn();
+            }
+        }
+        *board = Board::new(board.width(), board.hight());
+    }
+}
+
+impl BoardSnapshot {
+    /// treat every shape as placed, for snapshots taken as a shape locks
+    pub fn lock_shapes(&mut self) {
+        for shape in self.shapes.iter_mut() {
+            shape.active = false;
+        }
+    }
+
+    /// spawn the shapes and blocks onto `board`, which should be empty
+    pub fn rebuild(
+        &self,
+        commands: &mut Commands,
+        player: Entity,
+        board: &mut Board,
+        block_image: &BlockImage,
+        skin: BlockSkin,
+    ) {
+        let shapes = self
+            .shapes
+            .iter()
+            .map(|saved| {
+                let mut shape = commands.spawn((
+                    Shape {
+                        split: true,
+                        ..saved.shape.clone()
+                    },
+                    OnBoard(player),
+                ));
+                if saved.active {
+                    shape.insert(PlayerTarget::default());
+                }
+                shape.id()
+            })
+            .collect::<Vec<_>>();
+        for block in self.blocks.iter() {
+            spawn_board_block(
+                commands,
+                player,
+                board,
+                block.cell,
+                block_image.sprite(skin, block.color),
+                block.power,
+                Block {
+                    shape: block.shape.and_then(|index| shapes.get(index).copied()),
+                    moved: false,
+                    effects: HashSet::with_hasher(FixedHasher),
+                },
+            );
+        }
+    }
+}
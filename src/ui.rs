@@ -1,13 +1,13 @@
 use std::borrow::Cow;
 
-use crate::prelude::*;
+use crate::{player::Player, prelude::*};
 use bevy::{ecs::system::SystemId, prelude::*};
 use bevy_pkv::PkvStore;
 
 pub fn plugin(app: &mut App) {
     app.init_resource::<FontData>()
         .init_resource::<UiPalette>()
-        .add_plugins((menus::plugin, widgets::plugin))
+        .add_plugins((menus::plugin, widgets::plugin, report::plugin))
         .add_systems(
             Update,
             (
@@ -20,8 +20,11 @@ pub fn plugin(app: &mut App) {
             (fill_text, ui_hover, run_button_clicks, update_palette),
         )
         .add_systems(PostUpdate, set_font_size)
-        .add_systems(OnEnter(GameState::Playing), spawn_score)
-        .add_systems(Update, update_score.run_if(resource_changed::<Score>))
+        .add_systems(
+            OnEnter(GameState::Playing),
+            (spawn_score, spawn_mode_hud).after(crate::player::spawn_players),
+        )
+        .add_systems(Update, (update_score, update_mode_hud))
         .register_type::<MyText>()
         .register_type::<MyFont>();
 }
@@ -296,8 +299,13 @@ impl From<char> for FontIndex {
     }
 }
 
+/// shows the score of this player
+#[derive(Component)]
+struct ScoreBoard(Entity);
+
+/// holds one line of text for each of the current modes hud fields
 #[derive(Component)]
-struct ScoreBoard;
+struct ModeHud;
 
 #[derive(Component, Clone, Copy, Reflect)]
 enum MyFont {
@@ -487,23 +495,101 @@ fn run_button_clicks(
     }
 }
 
+mod report;
 mod widgets;
 
-fn spawn_score(mut commands: Commands, score: Res<Score>) {
-    commands.spawn((
-        ScoreBoard,
-        MyText(score.0.to_string().into()),
-        Node {
-            left: Val::Percent(5.),
-            top: Val::Percent(20.),
-            ..Default::default()
-        },
-        BorderRadius::all(Val::Px(20.)),
-    ));
+fn spawn_score(
+    mut commands: Commands,
+    players: Query<(Entity, &Score, Option<&crate::player::PlayerCamera>), With<Player>>,
+) {
+    for (player, score, camera) in &players {
+        let board = commands
+            .spawn((
+                ScoreBoard(player),
+                MyText(score.0.to_string().into()),
+                Node {
+                    left: Val::Percent(5.),
+                    top: Val::Percent(20.),
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                BorderRadius::all(Val::Px(20.)),
+                StateScoped(GameState::Playing),
+            ))
+            .id();
+        // each player sees their own score in their half of the window
+        if let Some(camera) = camera {
+            commands.entity(board).insert(UiTargetCamera(camera.0));
+        }
+    }
 }
 
-fn update_score(score: Res<Score>, mut board: Query<&mut MyText, With<ScoreBoard>>) {
-    for mut board in &mut board {
-        board.0 = score.0.to_string().into()
+fn update_score(
+    scores: Query<&Score, Changed<Score>>,
+    mut boards: Query<(&ScoreBoard, &mut MyText)>,
+) {
+    for (board, mut text) in &mut boards {
+        let Ok(score) = scores.get(board.0) else {
+            continue;
+        };
+        text.0 = score.0.to_string().into()
     }
 }
+
+fn spawn_mode_hud(
+    mut commands: Commands,
+    mode: Res<crate::mode::GameMode>,
+    progress: Res<crate::mode::ModeProgress>,
+    players: Query<(&Player, &Score)>,
+) {
+    let score = first_score(&players);
+    commands
+        .spawn((
+            ModeHud,
+            Node {
+                left: Val::Percent(5.),
+                top: Val::Percent(30.),
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            StateScoped(GameState::Playing),
+        ))
+        .with_children(|commands| {
+            for line in mode.hud(&progress, score) {
+                commands.spawn(MyText(line.into()));
+            }
+        });
+}
+
+fn update_mode_hud(
+    mode: Res<crate::mode::GameMode>,
+    progress: Res<crate::mode::ModeProgress>,
+    players: Query<(&Player, &Score)>,
+    changed: Query<(), (With<Player>, Changed<Score>)>,
+    huds: Query<&Children, With<ModeHud>>,
+    mut text: Query<&mut MyText>,
+) {
+    if !progress.is_changed() && changed.is_empty() {
+        return;
+    }
+    let lines = mode.hud(&progress, first_score(&players));
+    for children in &huds {
+        for (child, line) in children.into_iter().zip(&lines) {
+            let Ok(mut text) = text.get_mut(*child) else {
+                continue;
+            };
+            if text.0 != *line {
+                text.0 = line.clone().into();
+            }
+        }
+    }
+}
+
+/// modes are scored by player one
+fn first_score(players: &Query<(&Player, &Score)>) -> i32 {
+    players
+        .iter()
+        .find(|(player, _)| player.index == 0)
+        .map_or(0, |(_, score)| score.0)
+}
@@ -1,17 +1,37 @@
 use crate::prelude::*;
 use bevy::prelude::*;
+mod block_palette;
+mod deck_select;
+mod draft;
 mod main;
+mod mode_select;
+mod online;
 mod options;
+mod pause;
+mod puzzles;
+mod replays;
+mod shape_editor;
 mod ui_palette;
+mod versus;
 
 #[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone, Component)]
 pub enum Menu {
     #[default]
     Main,
+    ModeSelect,
+    DeckSelect,
     Options,
     KeyBinding,
     UiPalette,
+    BlockPalette,
     Pause,
+    Draft,
+    ShapeEditor,
+    Replays,
+    Versus,
+    Host,
+    Join,
+    Puzzles,
     None,
 }
 
@@ -20,7 +40,21 @@ pub fn plugin(app: &mut App) {
         .init_state::<Menu>()
         .add_systems(OnExit(GameState::InMenu), set_none)
         .add_systems(OnEnter(GameState::InMenu), pause_time)
-        .add_plugins((main::plugin, options::plugin, ui_palette::plugin));
+        .add_plugins((
+            main::plugin,
+            options::plugin,
+            ui_palette::plugin,
+            draft::plugin,
+            deck_select::plugin,
+            block_palette::plugin,
+            shape_editor::plugin,
+            mode_select::plugin,
+            replays::plugin,
+            versus::plugin,
+            online::plugin,
+            puzzles::plugin,
+            pause::plugin,
+        ));
 }
 
 pub fn set_none(mut next: ResMut<NextState<Menu>>, mut time: ResMut<Time<Virtual>>) {
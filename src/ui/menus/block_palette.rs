@@ -0,0 +1,146 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::board::Shape;
+use crate::deck::Deck;
+use crate::palette::{BlockPalette, PalettePreset};
+use crate::ui::widgets::{ColorWidget, ShapePreview};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::BlockPalette), spawn_block_palette_menu)
+        .add_systems(
+            Update,
+            update_previews
+                .run_if(resource_changed::<BlockPalette>.and(in_state(Menu::BlockPalette))),
+        );
+}
+
+/// the shape from the deck that a preview shows, kept so the preview can be recoloured
+#[derive(Component)]
+struct PaletteEntry(Shape);
+
+fn spawn_block_palette_menu(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    blocks: Res<BlockPalette>,
+    deck: Res<Deck>,
+) {
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Options);
+    });
+    let presets = PalettePreset::ALL.map(|preset| {
+        let on_click = commands.register_system(move |mut blocks: ResMut<BlockPalette>| {
+            *blocks = BlockPalette::preset(preset);
+        });
+        (preset.name(), on_click)
+    });
+    let mut entries = Vec::with_capacity(deck.shapes().len());
+    for shape in deck.shapes() {
+        let on_click = commands.register_system({
+            let shape = shape.clone();
+            move |mut commands: Commands, blocks: Res<BlockPalette>| {
+                let on_submit = commands.register_system({
+                    let shape = shape.clone();
+                    move |input: In<Color>, mut blocks: ResMut<BlockPalette>| {
+                        blocks.set(&shape, *input);
+                    }
+                });
+                commands.spawn((
+                    ColorWidget {
+                        current: blocks.color(&shape).to_linear(),
+                        on_submit,
+                    },
+                    Name::new("Shape Color"),
+                ));
+            }
+        });
+        entries.push((shape.clone(), on_click));
+    }
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                margin: UiRect::all(Val::Auto),
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::BlockPalette),
+        ))
+        .with_children(|commands| {
+            commands
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceAround,
+                    ..Default::default()
+                })
+                .with_children(|commands| {
+                    for (name, on_click) in presets {
+                        commands.spawn((
+                            menu_button_node(),
+                            menu_boarder(),
+                            Button,
+                            MenuButton {
+                                cleanup: true,
+                                on_click,
+                            },
+                            BackgroundColor(palette.button_color),
+                            MyText(name.into()),
+                        ));
+                    }
+                    commands.spawn((
+                        menu_button_node(),
+                        menu_boarder(),
+                        Button,
+                        MenuButton {
+                            cleanup: true,
+                            on_click: back,
+                        },
+                        BackgroundColor(palette.button_color),
+                        MyText("Back".into()),
+                    ));
+                });
+            commands
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::SpaceAround,
+                    ..Default::default()
+                })
+                .with_children(|commands| {
+                    for (shape, on_click) in entries {
+                        let mut preview = shape.clone();
+                        preview.color = blocks.color(&shape);
+                        commands.spawn((
+                            Node {
+                                padding: UiRect::all(Val::Px(5.)),
+                                margin: UiRect::all(Val::Px(5.)),
+                                ..Default::default()
+                            },
+                            menu_boarder(),
+                            Button,
+                            MenuButton {
+                                cleanup: true,
+                                on_click,
+                            },
+                            BackgroundColor(palette.button_color),
+                            ShapePreview {
+                                shape: preview,
+                                cell: 16.,
+                            },
+                            PaletteEntry(shape),
+                        ));
+                    }
+                });
+        });
+}
+
+fn update_previews(
+    mut previews: Query<(&PaletteEntry, &mut ShapePreview)>,
+    blocks: Res<BlockPalette>,
+) {
+    for (PaletteEntry(shape), mut preview) in &mut previews {
+        preview.shape.color = blocks.color(shape);
+    }
+}
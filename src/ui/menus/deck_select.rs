@@ -0,0 +1,119 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::board::Board;
+use crate::deck::{deck_list, find_deck, CustomDeck, Deck, DeckAsset, DeckReport, SelectedDeck};
+use crate::player::PlayerSetup;
+use crate::ui::widgets::ShapePreview;
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::DeckSelect), spawn_deck_select);
+}
+
+fn spawn_deck_select(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    decks: Res<Assets<DeckAsset>>,
+    custom: Res<CustomDeck>,
+    selected: Res<SelectedDeck>,
+) {
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::ModeSelect);
+    });
+    let mut buttons = Vec::new();
+    for (name, deck) in deck_list(&decks, &custom) {
+        let on_click = commands.register_system({
+            let name = name.clone();
+            move |mut commands: Commands,
+                  decks: Res<Assets<DeckAsset>>,
+                  custom: Res<CustomDeck>,
+                  mut deck: ResMut<Deck>,
+                  mut selected: ResMut<SelectedDeck>,
+                  mut report: ResMut<DeckReport>,
+                  mut state: ResMut<NextState<GameState>>| {
+                match find_deck(&name, &decks, &custom, &Board::default()) {
+                    Ok(found) => {
+                        commands.remove_resource::<crate::run::Run>();
+                        commands.insert_resource(PlayerSetup::default());
+                        *deck = found;
+                        selected.0 = name.clone();
+                        state.set(GameState::Playing);
+                    }
+                    Err(problems) => {
+                        *report = DeckReport {
+                            deck: name.clone(),
+                            problems,
+                        };
+                    }
+                }
+            }
+        });
+        buttons.push((name, deck, on_click));
+    }
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(40.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::DeckSelect),
+        ))
+        .with_children(|commands| {
+            for (name, deck, on_click) in buttons {
+                let text = if name == selected.0 {
+                    format!("{name} +")
+                } else {
+                    name
+                };
+                commands
+                    .spawn((
+                        menu_button_node(),
+                        menu_boarder(),
+                        Button,
+                        MenuButton {
+                            cleanup: true,
+                            on_click,
+                        },
+                        BackgroundColor(palette.button_color),
+                    ))
+                    .with_children(|commands| {
+                        commands.spawn((
+                            Node {
+                                margin: UiRect::vertical(Val::Auto),
+                                ..Default::default()
+                            },
+                            MyText(text.into()),
+                        ));
+                        for shape in deck.shapes().iter().take(5) {
+                            commands.spawn((
+                                Node {
+                                    margin: UiRect::left(Val::Px(10.)),
+                                    ..Default::default()
+                                },
+                                ShapePreview {
+                                    shape: shape.clone(),
+                                    cell: 8.,
+                                },
+                            ));
+                        }
+                    });
+            }
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: back,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("Back".into()),
+            ));
+        });
+}
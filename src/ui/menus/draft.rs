@@ -0,0 +1,98 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::deck::Deck;
+use crate::run::{draft_offers, Run};
+use crate::ui::widgets::ShapePreview;
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Draft), spawn_draft_menu);
+}
+
+fn spawn_draft_menu(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    deck: Res<Deck>,
+    run: Res<Run>,
+) {
+    let offers = draft_offers(&deck, &mut rand::rng());
+    let mut buttons = Vec::with_capacity(offers.len() + 1);
+    for offer in offers {
+        let preview = offer.preview(&deck);
+        let label = offer.label();
+        let on_click = commands.register_system(
+            move |mut deck: ResMut<Deck>,
+                  mut run: ResMut<Run>,
+                  mut state: ResMut<NextState<GameState>>| {
+                offer.apply(&mut deck);
+                next_stage(&mut run, &mut state);
+            },
+        );
+        buttons.push((label, preview, on_click));
+    }
+    let skip = commands.register_system(
+        |mut run: ResMut<Run>, mut state: ResMut<NextState<GameState>>| {
+            next_stage(&mut run, &mut state);
+        },
+    );
+    buttons.push(("Skip".to_string(), None, skip));
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(30.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::Draft),
+        ))
+        .with_children(|commands| {
+            commands.spawn((
+                Node {
+                    margin: UiRect::horizontal(Val::Auto),
+                    ..Default::default()
+                },
+                MyText(format!("Stage {} Clear", run.stage + 1).into()),
+            ));
+            for (label, preview, on_click) in buttons {
+                commands
+                    .spawn((
+                        menu_button_node(),
+                        menu_boarder(),
+                        Button,
+                        MenuButton {
+                            cleanup: true,
+                            on_click,
+                        },
+                        BackgroundColor(palette.button_color),
+                    ))
+                    .with_children(|commands| {
+                        commands.spawn((
+                            Node {
+                                margin: UiRect::vertical(Val::Auto),
+                                ..Default::default()
+                            },
+                            MyText(label.into()),
+                        ));
+                        if let Some(shape) = preview {
+                            commands.spawn((
+                                Node {
+                                    margin: UiRect::left(Val::Px(20.)),
+                                    ..Default::default()
+                                },
+                                ShapePreview { shape, cell: 12. },
+                            ));
+                        }
+                    });
+            }
+        });
+}
+
+fn next_stage(run: &mut Run, state: &mut NextState<GameState>) {
+    run.next_stage();
+    state.set(GameState::Playing);
+}
@@ -6,9 +6,30 @@ pub fn plugin(app: &mut App) {
     app.add_systems(OnEnter(menus::Menu::Main), open_main_menu);
 }
 
-fn open_main_menu(mut commands: Commands, palette: Res<UiPalette>) {
-    let play = commands.register_system(|mut state: ResMut<NextState<GameState>>| {
-        state.set(GameState::Playing);
+fn open_main_menu(mut commands: Commands, palette: Res<UiPalette>, store: Res<bevy_pkv::PkvStore>) {
+    let resume = crate::save::has_saved_game(&store)
+        .then(|| commands.register_system(crate::save::continue_game));
+    let play = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::ModeSelect);
+    });
+    let versus = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Versus);
+    });
+    let host = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Host);
+    });
+    let join = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Join);
+    });
+    let run = commands.register_system(crate::run::start_run);
+    let dig = commands.register_system(crate::dig::start_dig);
+    let practice = commands.register_system(crate::practice::start_practice);
+    let puzzles = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Puzzles);
+    });
+    let demo = commands.register_system(crate::ai::start_demo);
+    let replays = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Replays);
     });
     let options = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
         state.set(Menu::Options);
@@ -32,6 +53,19 @@ fn open_main_menu(mut commands: Commands, palette: Res<UiPalette>) {
             StateScoped(Menu::Main),
         ))
         .with_children(|commands| {
+            if let Some(on_click) = resume {
+                commands.spawn((
+                    menu_button_node(),
+                    menu_boarder(),
+                    Button,
+                    MenuButton {
+                        cleanup: true,
+                        on_click,
+                    },
+                    BackgroundColor(palette.button_color),
+                    MyText("CONTINUE".into()),
+                ));
+            }
             commands.spawn((
                 menu_button_node(),
                 menu_boarder(),
@@ -43,6 +77,105 @@ fn open_main_menu(mut commands: Commands, palette: Res<UiPalette>) {
                 BackgroundColor(palette.button_color),
                 MyText("PLAY".into()),
             ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: versus,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("VERSUS".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: host,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("HOST".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: join,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("JOIN".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: run,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("RUN".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: dig,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("DIG".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: practice,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("PRACTICE".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: puzzles,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("PUZZLES".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: demo,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("DEMO".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: replays,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("REPLAYS".into()),
+            ));
             commands.spawn((
                 menu_button_node(),
                 menu_boarder(),
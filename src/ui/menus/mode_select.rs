@@ -0,0 +1,68 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::mode::{GameMode, ModeRecords};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::ModeSelect), spawn_mode_select);
+}
+
+fn spawn_mode_select(mut commands: Commands, palette: Res<UiPalette>, records: Res<ModeRecords>) {
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Main);
+    });
+    let mut buttons = Vec::new();
+    for mode in GameMode::ALL {
+        let on_click = commands.register_system(
+            move |mut current: ResMut<GameMode>, mut state: ResMut<NextState<Menu>>| {
+                *current = mode;
+                state.set(Menu::DeckSelect);
+            },
+        );
+        let text = match records.best(mode) {
+            Some(best) => format!("{} - {}", mode.name(), best.label()),
+            None => mode.name().to_string(),
+        };
+        buttons.push((text, on_click));
+    }
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(40.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::ModeSelect),
+        ))
+        .with_children(|commands| {
+            for (text, on_click) in buttons {
+                commands.spawn((
+                    menu_button_node(),
+                    menu_boarder(),
+                    Button,
+                    MenuButton {
+                        cleanup: true,
+                        on_click,
+                    },
+                    BackgroundColor(palette.button_color),
+                    MyText(text.into()),
+                ));
+            }
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: back,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("Back".into()),
+            ));
+        });
+}
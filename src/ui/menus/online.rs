@@ -0,0 +1,140 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::net::{close_lobby, host, join, NetLobby, DEFAULT_PORT};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Host), open_host)
+        .add_systems(OnEnter(Menu::Join), open_join)
+        .add_systems(OnExit(Menu::Host), close_lobby)
+        .add_systems(OnExit(Menu::Join), close_lobby)
+        .add_systems(
+            Update,
+            (
+                type_address,
+                spawn_lobby.run_if(resource_changed::<NetLobby>),
+            )
+                .chain()
+                .run_if(in_state(Menu::Host).or(in_state(Menu::Join))),
+        );
+}
+
+#[derive(Component)]
+struct LobbyRoot;
+
+fn open_host(mut lobby: ResMut<NetLobby>) {
+    lobby.address = DEFAULT_PORT.to_string();
+}
+
+fn open_join(mut lobby: ResMut<NetLobby>) {
+    lobby.address = format!("127.0.0.1 {DEFAULT_PORT}");
+}
+
+fn type_address(mut keys: EventReader<KeyboardInput>, mut lobby: ResMut<NetLobby>) {
+    for key in keys.read() {
+        if !key.state.is_pressed() {
+            continue;
+        }
+        match &key.logical_key {
+            Key::Character(c) => lobby.address.extend(
+                c.chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-'),
+            ),
+            Key::Space => lobby.address.push(' '),
+            Key::Backspace => {
+                lobby.address.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn spawn_lobby(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    lobby: Res<NetLobby>,
+    menu: Res<State<Menu>>,
+    roots: Query<Entity, With<LobbyRoot>>,
+) {
+    for root in &roots {
+        commands.entity(root).despawn();
+    }
+    let hosting = *menu.get() == Menu::Host;
+    let (title, field, action) = if hosting {
+        ("Host", "Port", "Host")
+    } else {
+        ("Join", "Address", "Join")
+    };
+    let start = if hosting {
+        commands.register_system(|mut commands: Commands, mut lobby: ResMut<NetLobby>| {
+            close_lobby(commands.reborrow());
+            host(&mut commands, &mut lobby);
+        })
+    } else {
+        commands.register_system(|mut commands: Commands, mut lobby: ResMut<NetLobby>| {
+            close_lobby(commands.reborrow());
+            join(&mut commands, &mut lobby);
+        })
+    };
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Main);
+    });
+    let menu = menu.get().clone();
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(40.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(menu),
+            LobbyRoot,
+        ))
+        .with_children(|commands| {
+            commands.spawn((
+                Node {
+                    margin: UiRect::horizontal(Val::Auto),
+                    ..Default::default()
+                },
+                MyText(title.into()),
+            ));
+            commands.spawn((
+                Node {
+                    margin: UiRect::horizontal(Val::Auto),
+                    ..Default::default()
+                },
+                MyText(format!("{field} {}", lobby.address).into()),
+            ));
+            if let Some(status) = &lobby.status {
+                commands.spawn((
+                    Node {
+                        margin: UiRect::horizontal(Val::Auto),
+                        flex_wrap: FlexWrap::Wrap,
+                        ..Default::default()
+                    },
+                    MyText(status.clone().into()),
+                    MyFont::Custom(15.),
+                ));
+            }
+            for (label, on_click) in [(action, start), ("Back", back)] {
+                commands.spawn((
+                    menu_button_node(),
+                    menu_boarder(),
+                    Button,
+                    MenuButton {
+                        cleanup: true,
+                        on_click,
+                    },
+                    BackgroundColor(palette.button_color),
+                    MyText(label.into()),
+                ));
+            }
+        });
+}
@@ -1,12 +1,53 @@
+use crate::ai::AiDifficulty;
+use crate::blocks::BlockSkin;
 use crate::ui::*;
 
 use super::{menu_boarder, menu_button_node, Menu};
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(menus::Menu::Options), spawn_options_menu);
+    app.add_systems(OnEnter(menus::Menu::Options), spawn_options_menu)
+        .add_systems(
+            Update,
+            update_skin_label.run_if(resource_changed::<BlockSkin>.and(in_state(Menu::Options))),
+        )
+        .add_systems(
+            Update,
+            update_ai_label.run_if(resource_changed::<AiDifficulty>.and(in_state(Menu::Options))),
+        );
 }
 
-fn spawn_options_menu(mut commands: Commands, palette: Res<UiPalette>) {
+#[derive(Component)]
+struct SkinLabel;
+
+fn skin_label(skin: BlockSkin) -> String {
+    format!("Skin {}", skin.name())
+}
+
+fn update_skin_label(skin: Res<BlockSkin>, mut labels: Query<&mut MyText, With<SkinLabel>>) {
+    for mut label in &mut labels {
+        label.0 = skin_label(*skin).into();
+    }
+}
+
+#[derive(Component)]
+struct AiLabel;
+
+fn ai_label(difficulty: AiDifficulty) -> String {
+    format!("AI {}", difficulty.name())
+}
+
+fn update_ai_label(difficulty: Res<AiDifficulty>, mut labels: Query<&mut MyText, With<AiLabel>>) {
+    for mut label in &mut labels {
+        label.0 = ai_label(*difficulty).into();
+    }
+}
+
+fn spawn_options_menu(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    skin: Res<BlockSkin>,
+    difficulty: Res<AiDifficulty>,
+) {
     let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
         state.set(Menu::Main);
     });
@@ -16,6 +57,18 @@ fn spawn_options_menu(mut commands: Commands, palette: Res<UiPalette>) {
     let palette_id = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
         state.set(Menu::UiPalette);
     });
+    let block_palette = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::BlockPalette);
+    });
+    let next_skin = commands.register_system(|mut skin: ResMut<BlockSkin>| {
+        *skin = skin.next();
+    });
+    let next_difficulty = commands.register_system(|mut difficulty: ResMut<AiDifficulty>| {
+        *difficulty = difficulty.next();
+    });
+    let editor = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::ShapeEditor);
+    });
     let text_up = commands.register_system(|mut text_size: ResMut<FontData>| {
         text_size.font_size = text_size.font_size.next();
     });
@@ -105,6 +158,52 @@ fn spawn_options_menu(mut commands: Commands, palette: Res<UiPalette>) {
                 BackgroundColor(palette.button_color),
                 MyText("Palette".into()),
             ));
+            commands.spawn((
+                menu_button_node(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: editor,
+                },
+                menu_boarder(),
+                BackgroundColor(palette.button_color),
+                MyText("Shape Editor".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: block_palette,
+                },
+                menu_boarder(),
+                BackgroundColor(palette.button_color),
+                MyText("Block Colours".into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: next_skin,
+                },
+                menu_boarder(),
+                BackgroundColor(palette.button_color),
+                SkinLabel,
+                MyText(skin_label(*skin).into()),
+            ));
+            commands.spawn((
+                menu_button_node(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: next_difficulty,
+                },
+                menu_boarder(),
+                BackgroundColor(palette.button_color),
+                AiLabel,
+                MyText(ai_label(*difficulty).into()),
+            ));
             commands.spawn((
                 menu_button_node(),
                 Button,
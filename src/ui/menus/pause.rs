@@ -0,0 +1,76 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Pause), spawn_pause_menu)
+        .add_systems(
+            Update,
+            (
+                open_pause.run_if(
+                    in_state(GameState::Playing)
+                        .and(in_state(Menu::None))
+                        // the other player can't be stopped
+                        .and(not(resource_exists::<crate::net::NetGame>)),
+                ),
+                close_pause.run_if(in_state(Menu::Pause)),
+            ),
+        );
+}
+
+fn open_pause(
+    input: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<NextState<Menu>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if input.just_pressed(KeyCode::Escape) {
+        menu.set(Menu::Pause);
+        time.pause();
+    }
+}
+
+fn close_pause(
+    input: Res<ButtonInput<KeyCode>>,
+    next: ResMut<NextState<Menu>>,
+    time: ResMut<Time<Virtual>>,
+) {
+    if input.just_pressed(KeyCode::Escape) {
+        super::set_none(next, time);
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands, palette: Res<UiPalette>) {
+    let buttons = [
+        ("Resume", commands.register_system(super::set_none)),
+        ("Quit", commands.register_system(crate::save::quit_game)),
+    ];
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(30.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::Pause),
+        ))
+        .with_children(|commands| {
+            for (label, on_click) in buttons {
+                commands.spawn((
+                    menu_button_node(),
+                    menu_boarder(),
+                    Button,
+                    MenuButton {
+                        cleanup: true,
+                        on_click,
+                    },
+                    BackgroundColor(palette.button_color),
+                    MyText(label.into()),
+                ));
+            }
+        });
+}
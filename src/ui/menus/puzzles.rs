@@ -0,0 +1,71 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::puzzle::{start_puzzle, Puzzle, PuzzleRecords};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Puzzles), spawn_puzzle_select);
+}
+
+fn spawn_puzzle_select(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    puzzles: Res<Assets<Puzzle>>,
+    records: Res<PuzzleRecords>,
+) {
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Main);
+    });
+    let mut puzzles = puzzles.iter().map(|(_, puzzle)| puzzle).collect::<Vec<_>>();
+    puzzles.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut buttons = Vec::new();
+    for puzzle in puzzles {
+        let on_click = commands.register_system(start_puzzle(puzzle.clone()));
+        let text = if records.completed(&puzzle.name) {
+            format!("{} - {} - Done", puzzle.name, puzzle.goal.describe())
+        } else {
+            format!("{} - {}", puzzle.name, puzzle.goal.describe())
+        };
+        buttons.push((text, on_click));
+    }
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(40.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::Puzzles),
+        ))
+        .with_children(|commands| {
+            for (text, on_click) in buttons {
+                commands.spawn((
+                    menu_button_node(),
+                    menu_boarder(),
+                    Button,
+                    MenuButton {
+                        cleanup: true,
+                        on_click,
+                    },
+                    BackgroundColor(palette.button_color),
+                    MyText(text.into()),
+                ));
+            }
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: back,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("Back".into()),
+            ));
+        });
+}
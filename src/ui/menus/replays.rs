@@ -0,0 +1,262 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::replay::{
+    delete_replay, list_replays, load_header, load_replay, rename_replay, ReplayError,
+};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<ReplayBrowser>()
+        .add_systems(OnEnter(Menu::Replays), open_browser)
+        .add_systems(
+            Update,
+            (
+                type_name.run_if(|browser: Res<ReplayBrowser>| browser.renaming.is_some()),
+                spawn_browser.run_if(resource_changed::<ReplayBrowser>),
+            )
+                .chain()
+                .run_if(in_state(Menu::Replays)),
+        );
+}
+
+#[derive(Resource, Default)]
+struct ReplayBrowser {
+    /// the replay being renamed and the name typed so far
+    renaming: Option<(String, String)>,
+    /// the last thing that went wrong, shown at the top of the list
+    error: Option<String>,
+}
+
+impl ReplayBrowser {
+    fn report(&mut self, e: ReplayError) {
+        error!("{e}");
+        self.error = Some(e.to_string());
+    }
+}
+
+#[derive(Component)]
+struct BrowserRoot;
+
+fn open_browser(mut browser: ResMut<ReplayBrowser>) {
+    *browser = ReplayBrowser::default();
+}
+
+fn type_name(mut keys: EventReader<KeyboardInput>, mut browser: ResMut<ReplayBrowser>) {
+    let mut done = None;
+    let Some((from, name)) = browser.renaming.as_mut() else {
+        return;
+    };
+    for key in keys.read() {
+        if !key.state.is_pressed() {
+            continue;
+        }
+        match &key.logical_key {
+            Key::Character(c) => name.extend(
+                c.chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == ' '),
+            ),
+            Key::Space => name.push(' '),
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Enter => done = Some(Some((from.clone(), name.clone()))),
+            Key::Escape => done = Some(None),
+            _ => {}
+        }
+    }
+    match done {
+        Some(Some((from, to))) => {
+            browser.renaming = None;
+            if let Err(e) = rename_replay(&from, to.trim()) {
+                browser.report(e);
+            }
+        }
+        Some(None) => browser.renaming = None,
+        None => {}
+    }
+}
+
+fn spawn_browser(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    browser: Res<ReplayBrowser>,
+    roots: Query<Entity, With<BrowserRoot>>,
+) {
+    for root in &roots {
+        commands.entity(root).despawn();
+    }
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Main);
+    });
+    let mut rows = Vec::new();
+    let mut error = browser.error.clone();
+    match list_replays() {
+        Ok(names) => {
+            for name in names {
+                let label = match load_header(&name) {
+                    Ok(header) => format!(
+                        "{name} - {} {} - {}",
+                        header.mode.name(),
+                        header.deck,
+                        header.score
+                    ),
+                    Err(ReplayError::Incompatible { game_version, .. }) => {
+                        format!("{name} - from version {game_version}")
+                    }
+                    Err(_) => format!("{name} - unreadable"),
+                };
+                let renaming = browser
+                    .renaming
+                    .as_ref()
+                    .filter(|(from, _)| *from == name)
+                    .map(|(_, to)| to.clone());
+                rows.push(row_buttons(&mut commands, name, label, renaming));
+            }
+        }
+        Err(e) => error = Some(e.to_string()),
+    }
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(50.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                overflow: Overflow::clip_y(),
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::Replays),
+            BrowserRoot,
+        ))
+        .with_children(|commands| {
+            if let Some(error) = error {
+                commands.spawn((
+                    Node {
+                        margin: UiRect::horizontal(Val::Auto),
+                        flex_wrap: FlexWrap::Wrap,
+                        ..Default::default()
+                    },
+                    MyText(error.into()),
+                    MyFont::Custom(15.),
+                ));
+            }
+            if rows.is_empty() {
+                commands.spawn((
+                    Node {
+                        margin: UiRect::horizontal(Val::Auto),
+                        ..Default::default()
+                    },
+                    MyText("No replays yet".into()),
+                ));
+            }
+            for (label, buttons) in rows {
+                commands
+                    .spawn(Node {
+                        margin: UiRect::horizontal(Val::Auto),
+                        width: Val::Percent(90.),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    })
+                    .with_children(|commands| {
+                        commands.spawn((
+                            Node {
+                                flex_grow: 1.,
+                                ..Default::default()
+                            },
+                            MyText(label.into()),
+                            MyFont::Custom(15.),
+                        ));
+                        for (text, on_click) in buttons {
+                            commands.spawn((
+                                Node {
+                                    width: Val::Auto,
+                                    margin: UiRect::left(Val::Px(5.)),
+                                    ..menu_button_node()
+                                },
+                                menu_boarder(),
+                                Button,
+                                MenuButton {
+                                    cleanup: true,
+                                    on_click,
+                                },
+                                BackgroundColor(palette.button_color),
+                                MyText(text.into()),
+                                MyFont::Custom(15.),
+                            ));
+                        }
+                    });
+            }
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: back,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("Back".into()),
+            ));
+        });
+}
+
+/// the label and buttons for one replay, the label turns into the new name while it is being renamed
+fn row_buttons(
+    commands: &mut Commands,
+    name: String,
+    label: String,
+    renaming: Option<String>,
+) -> (String, Vec<(&'static str, SystemId)>) {
+    if let Some(to) = renaming {
+        let save = commands.register_system(|mut browser: ResMut<ReplayBrowser>| {
+            let Some((from, to)) = browser.renaming.take() else {
+                return;
+            };
+            if let Err(e) = rename_replay(&from, to.trim()) {
+                browser.report(e);
+            }
+        });
+        let cancel = commands.register_system(|mut browser: ResMut<ReplayBrowser>| {
+            browser.renaming = None;
+        });
+        return (
+            format!("Rename to {to}"),
+            vec![("Save", save), ("Cancel", cancel)],
+        );
+    }
+    let play = commands.register_system({
+        let name = name.clone();
+        move |mut commands: Commands,
+              mut browser: ResMut<ReplayBrowser>,
+              mut state: ResMut<NextState<GameState>>| {
+            match load_replay(&name) {
+                Ok((_, replay)) => {
+                    crate::replay::watch(&mut commands, replay);
+                    state.set(GameState::Playing);
+                }
+                Err(e) => browser.report(e),
+            }
+        }
+    });
+    let rename = commands.register_system({
+        let name = name.clone();
+        move |mut browser: ResMut<ReplayBrowser>| {
+            browser.renaming = Some((name.clone(), name.clone()));
+        }
+    });
+    let delete = commands.register_system(move |mut browser: ResMut<ReplayBrowser>| {
+        match delete_replay(&name) {
+            Ok(()) => browser.set_changed(),
+            Err(e) => browser.report(e),
+        }
+    });
+    (
+        label,
+        vec![("Play", play), ("Rename", rename), ("Delete", delete)],
+    )
+}
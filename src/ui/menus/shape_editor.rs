@@ -0,0 +1,217 @@
+use super::{menu_boarder, menu_button_node, Menu};
+use crate::board::{Board, Shape};
+use crate::deck::{CustomDeck, DeckReport};
+use crate::ui::widgets::ColorWidget;
+use crate::ui::*;
+
+/// number of cells along each side of the drawing grid
+const GRID: i32 = 5;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::ShapeEditor), spawn_shape_editor)
+        .add_systems(
+            Update,
+            (
+                update_cells.run_if(resource_exists_and_changed::<EditorShape>),
+                update_count.run_if(resource_changed::<CustomDeck>),
+            )
+                .run_if(in_state(Menu::ShapeEditor)),
+        );
+}
+
+/// the shape being drawn, blocks are offsets from the middle of the grid
+#[derive(Resource)]
+struct EditorShape(Shape);
+
+impl Default for EditorShape {
+    fn default() -> Self {
+        EditorShape(Shape {
+            split: false,
+            center: IVec2::ZERO,
+            blocks: Vec::new(),
+            color: Color::WHITE,
+            center_of_mass: Vec2::ZERO,
+            pivot: Vec2::ZERO,
+            powers: Vec::new(),
+        })
+    }
+}
+
+#[derive(Component)]
+struct EditorCell(IVec2);
+
+#[derive(Component)]
+struct CustomCount;
+
+fn spawn_shape_editor(mut commands: Commands, palette: Res<UiPalette>, custom: Res<CustomDeck>) {
+    commands.insert_resource(EditorShape::default());
+    let back = commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+        state.set(Menu::Options);
+    });
+    let color = commands.register_system(|mut commands: Commands, editor: Res<EditorShape>| {
+        let on_submit =
+            commands.register_system(|input: In<Color>, mut editor: ResMut<EditorShape>| {
+                editor.0.color = *input;
+            });
+        commands.spawn((
+            ColorWidget {
+                current: editor.0.color.to_linear(),
+                on_submit,
+            },
+            Name::new("Shape Color"),
+        ));
+    });
+    let rotate = commands.register_system(|mut editor: ResMut<EditorShape>| {
+        if editor.0.blocks.is_empty() {
+            return;
+        }
+        // rotate on a board the size of the grid so the preview matches the game
+        let mut board = Board::new(GRID, GRID);
+        let shape = &mut editor.0;
+        shape.center = IVec2::splat(GRID / 2);
+        shape.calc_center();
+        if !shape.rotate(&mut board) {
+            warn!("Shape does not fit in the editor once rotated");
+        }
+        shape.center = IVec2::ZERO;
+    });
+    let clear = commands.register_system(|mut editor: ResMut<EditorShape>| {
+        editor.0.blocks.clear();
+    });
+    let save = commands.register_system(
+        |editor: Res<EditorShape>,
+         mut custom: ResMut<CustomDeck>,
+         mut report: ResMut<DeckReport>| {
+            let mut deck = custom.0.clone();
+            deck.add(editor.0.clone());
+            let problems = deck.validate(&Board::default());
+            if !problems.is_empty() {
+                *report = DeckReport {
+                    deck: "Custom".into(),
+                    problems,
+                };
+                return;
+            }
+            custom.0 = deck;
+        },
+    );
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                margin: UiRect::all(Val::Auto),
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::ShapeEditor),
+        ))
+        .with_children(|commands| {
+            commands
+                .spawn(Node {
+                    margin: UiRect::all(Val::Px(20.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                })
+                .with_children(|commands| {
+                    for y in (0..GRID).rev() {
+                        commands.spawn(Node::default()).with_children(|commands| {
+                            for x in 0..GRID {
+                                commands
+                                    .spawn((
+                                        Node {
+                                            width: Val::Px(48.),
+                                            height: Val::Px(48.),
+                                            border: UiRect::all(Val::Px(2.)),
+                                            ..Default::default()
+                                        },
+                                        BorderColor(Color::BLACK),
+                                        BackgroundColor(palette.button_color),
+                                        EditorCell(IVec2::new(x, y) - IVec2::splat(GRID / 2)),
+                                    ))
+                                    .observe(toggle_cell);
+                            }
+                        });
+                    }
+                });
+            commands
+                .spawn(Node {
+                    min_width: Val::Percent(30.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceAround,
+                    ..Default::default()
+                })
+                .with_children(|commands| {
+                    commands.spawn((
+                        Node {
+                            margin: UiRect::horizontal(Val::Auto),
+                            ..Default::default()
+                        },
+                        CustomCount,
+                        MyText(custom_count(&custom).into()),
+                    ));
+                    for (text, on_click) in [
+                        ("Colour", color),
+                        ("Rotate", rotate),
+                        ("Clear", clear),
+                        ("Save", save),
+                        ("Back", back),
+                    ] {
+                        commands.spawn((
+                            menu_button_node(),
+                            menu_boarder(),
+                            Button,
+                            MenuButton {
+                                cleanup: true,
+                                on_click,
+                            },
+                            BackgroundColor(palette.button_color),
+                            MyText(text.into()),
+                        ));
+                    }
+                });
+        });
+}
+
+fn custom_count(custom: &CustomDeck) -> String {
+    format!("{} custom shapes", custom.shapes().len())
+}
+
+fn toggle_cell(
+    trigger: Trigger<Pointer<Click>>,
+    cells: Query<&EditorCell>,
+    mut editor: ResMut<EditorShape>,
+) {
+    let Ok(EditorCell(cell)) = cells.get(trigger.target) else {
+        error!("{:?} is not an editor cell", trigger.target);
+        return;
+    };
+    if let Some(index) = editor.0.blocks.iter().position(|block| block == cell) {
+        editor.0.blocks.remove(index);
+    } else {
+        editor.0.blocks.push(*cell);
+    }
+}
+
+fn update_cells(
+    mut cells: Query<(&EditorCell, &mut BackgroundColor)>,
+    editor: Res<EditorShape>,
+    palette: Res<UiPalette>,
+) {
+    for (EditorCell(cell), mut bg) in &mut cells {
+        bg.0 = if editor.0.blocks.contains(cell) {
+            editor.0.color
+        } else {
+            palette.button_color
+        };
+    }
+}
+
+fn update_count(custom: Res<CustomDeck>, mut text: Query<&mut MyText, With<CustomCount>>) {
+    for mut text in &mut text {
+        text.0 = custom_count(&custom).into();
+    }
+}
@@ -0,0 +1,62 @@
+use crate::player::PlayerKind;
+use crate::ui::*;
+use crate::versus::start_versus;
+
+use super::{menu_boarder, menu_button_node, Menu};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Versus), spawn_versus_menu);
+}
+
+fn spawn_versus_menu(
+    mut commands: Commands,
+    palette: Res<UiPalette>,
+    difficulty: Res<crate::ai::AiDifficulty>,
+) {
+    let buttons = [
+        (
+            "2 Players".to_string(),
+            commands.register_system(start_versus(PlayerKind::Human)),
+        ),
+        (
+            format!("Vs AI {}", difficulty.name()),
+            commands.register_system(start_versus(PlayerKind::Ai(*difficulty))),
+        ),
+        (
+            "Back".to_string(),
+            commands.register_system(|mut state: ResMut<NextState<Menu>>| {
+                state.set(Menu::Main);
+            }),
+        ),
+    ];
+
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(75.),
+                min_width: Val::Percent(30.),
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceAround,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            StateScoped(Menu::Versus),
+        ))
+        .with_children(|commands| {
+            for (label, on_click) in buttons {
+                commands.spawn((
+                    menu_button_node(),
+                    menu_boarder(),
+                    Button,
+                    MenuButton {
+                        cleanup: true,
+                        on_click,
+                    },
+                    BackgroundColor(palette.button_color),
+                    MyText(label.into()),
+                ));
+            }
+        });
+}
@@ -0,0 +1,85 @@
+use crate::deck::DeckReport;
+use crate::ui::menus::{menu_boarder, menu_button_node};
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        show_deck_report.run_if(resource_changed::<DeckReport>),
+    );
+}
+
+#[derive(Component)]
+struct ReportPanel;
+
+/// list everything wrong with the last deck that was loaded over the top of whatever is open
+fn show_deck_report(
+    mut commands: Commands,
+    report: Res<DeckReport>,
+    panels: Query<Entity, With<ReportPanel>>,
+    palette: Res<UiPalette>,
+) {
+    for panel in &panels {
+        commands.entity(panel).despawn();
+    }
+    if report.problems.is_empty() {
+        return;
+    }
+    let close = commands.register_system(
+        |mut commands: Commands, panels: Query<Entity, With<ReportPanel>>| {
+            for panel in &panels {
+                commands.entity(panel).despawn();
+            }
+        },
+    );
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                right: Val::Px(10.),
+                width: Val::Percent(40.),
+                padding: UiRect::all(Val::Px(10.)),
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            BackgroundColor(palette.background),
+            BorderRadius::all(Val::Px(10.)),
+            GlobalZIndex(10),
+            ReportPanel,
+            Name::new("Deck Report"),
+        ))
+        .with_children(|commands| {
+            commands.spawn((
+                Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    ..Default::default()
+                },
+                MyText(format!("Deck {} has problems", report.deck).into()),
+                MyFont::Custom(15.),
+            ));
+            for problem in report.problems.iter() {
+                commands.spawn((
+                    Node {
+                        flex_wrap: FlexWrap::Wrap,
+                        margin: UiRect::top(Val::Px(5.)),
+                        ..Default::default()
+                    },
+                    MyText(problem.to_string().into()),
+                    MyFont::Custom(12.),
+                ));
+            }
+            commands.spawn((
+                menu_button_node(),
+                menu_boarder(),
+                Button,
+                MenuButton {
+                    cleanup: true,
+                    on_click: close,
+                },
+                BackgroundColor(palette.button_color),
+                MyText("OK".into()),
+                MyFont::Custom(15.),
+            ));
+        });
+}
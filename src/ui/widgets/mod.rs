@@ -1,10 +1,12 @@
 mod anchor_widget;
 mod color_widget;
+mod shape_preview;
 mod slider_widget;
 use bevy::prelude::*;
 
 pub use anchor_widget::AnchorWidget;
 pub use color_widget::ColorWidget;
+pub use shape_preview::ShapePreview;
 pub use slider_widget::SliderWidget;
 
 pub fn plugin(app: &mut App) {
@@ -12,6 +14,7 @@ pub fn plugin(app: &mut App) {
         color_widget::plugin,
         anchor_widget::plugin,
         slider_widget::plugin,
+        shape_preview::plugin,
     ));
 }
 
@@ -0,0 +1,63 @@
+use crate::board::Shape;
+use crate::ui::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Update, spawn_preview);
+}
+
+/// draws a small picture of a shape out of ui nodes
+#[derive(Component)]
+#[require(Node)]
+pub struct ShapePreview {
+    pub shape: Shape,
+    /// size of each block in pixels
+    pub cell: f32,
+}
+
+fn spawn_preview(
+    previews: Query<(Entity, &ShapePreview), Changed<ShapePreview>>,
+    mut commands: Commands,
+) {
+    for (entity, preview) in &previews {
+        commands.entity(entity).despawn_related::<Children>();
+        let Some(min) = preview.shape.blocks.iter().copied().reduce(IVec2::min) else {
+            continue;
+        };
+        let max = preview
+            .shape
+            .blocks
+            .iter()
+            .copied()
+            .reduce(IVec2::max)
+            .unwrap_or(min);
+        let size = (max - min + IVec2::ONE).as_vec2() * preview.cell;
+        commands.entity(entity).with_children(|commands| {
+            commands
+                .spawn(Node {
+                    width: Val::Px(size.x),
+                    height: Val::Px(size.y),
+                    margin: UiRect::all(Val::Auto),
+                    ..Default::default()
+                })
+                .with_children(|commands| {
+                    for (index, block) in preview.shape.blocks.iter().enumerate() {
+                        let offset = (block - min).as_vec2() * preview.cell;
+                        let powered = preview.shape.powers.iter().any(|(i, _)| *i == index);
+                        commands.spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(offset.x),
+                                bottom: Val::Px(offset.y),
+                                width: Val::Px(preview.cell),
+                                height: Val::Px(preview.cell),
+                                border: UiRect::all(Val::Px(preview.cell * 0.15)),
+                                ..Default::default()
+                            },
+                            BackgroundColor(preview.shape.color),
+                            BorderColor(if powered { Color::WHITE } else { Color::BLACK }),
+                        ));
+                    }
+                });
+        });
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    board::{on_step, Board, GameClock, LinesCleared, OnBoard, Shape},
+    deck::PlayerTarget,
+    garbage::AddGarbage,
+    player::{Player, PlayerKind, PlayerSetup},
+    prelude::*,
+    save::Cleared,
+};
+
+mod attack;
+
+pub use attack::{AttackTable, AttackTableHandle, Clear};
+
+pub fn plugin(app: &mut App) {
+    app.add_plugins(attack::plugin)
+        .add_event::<AttackSent>()
+        .add_observer(lock_shape)
+        .add_systems(
+            OnEnter(GameState::Playing),
+            setup_versus
+                .after(crate::player::spawn_players)
+                .run_if(is_versus),
+        )
+        .add_systems(
+            FixedLast,
+            (count_clears, send_attacks, raise_garbage)
+                .chain()
+                .run_if(in_state(GameState::Playing).and(on_step)),
+        )
+        .add_systems(Update, update_meters.run_if(in_state(GameState::Playing)));
+}
+
+/// how wide the incoming meter is drawn
+const METER_WIDTH: f32 = 12.;
+
+/// start a game with two boards side by side
+pub fn start_versus(opponent: PlayerKind) -> impl FnMut(Commands, ResMut<NextState<GameState>>) {
+    move |mut commands, mut state| {
+        commands.remove_resource::<crate::run::Run>();
+        commands.remove_resource::<crate::dig::Dig>();
+        commands.insert_resource(crate::mode::GameMode::Endless);
+        commands.insert_resource(PlayerSetup::versus(PlayerKind::Human, opponent));
+        state.set(GameState::Playing);
+    }
+}
+
+fn is_versus(setup: Res<PlayerSetup>) -> bool {
+    setup.is_versus()
+}
+
+/// how a player has been clearing lines, needed for spins, combos and back to backs
+#[derive(Component, Default)]
+pub struct AttackState {
+    /// lines cleared since the board last settled
+    lines: i32,
+    /// the last shape to lock was spun into place
+    spin: bool,
+    /// clears in a row so far
+    combo: i32,
+    /// the last shape to lock cleared something
+    cleared: bool,
+    /// the last clear was a spin or four lines or more
+    back_to_back: bool,
+    /// tick the last shape locked on, the pieces of a split shape lock together
+    locked_on: Option<u64>,
+}
+
+/// garbage sent to a player that has not risen yet, attacks cancel it from the front
+#[derive(Component, Default)]
+pub struct IncomingGarbage {
+    /// rows and the tick they rise on
+    queue: VecDeque<(i32, u64)>,
+}
+
+impl IncomingGarbage {
+    pub fn rows(&self) -> i32 {
+        self.queue.iter().map(|(rows, _)| rows).sum()
+    }
+
+    /// use an attack to cancel queued garbage, returns what is left to send on
+    fn cancel(&mut self, mut attack: i32) -> i32 {
+        while attack > 0 {
+            let Some((rows, _)) = self.queue.front_mut() else {
+                break;
+            };
+            let cancelled = attack.min(*rows);
+            *rows -= cancelled;
+            attack -= cancelled;
+            if *rows == 0 {
+                self.queue.pop_front();
+            }
+        }
+        attack
+    }
+}
+
+/// garbage a player sent after cancelling their own
+#[derive(Event)]
+pub struct AttackSent {
+    pub from: Entity,
+    pub rows: i32,
+}
+
+/// shows how much garbage is waiting next to a players board
+#[derive(Component)]
+struct IncomingMeter(Entity);
+
+fn setup_versus(mut commands: Commands, players: Query<Entity, With<Player>>) {
+    for player in &players {
+        let meter = commands
+            .spawn((
+                IncomingMeter(player),
+                Sprite {
+                    color: Color::srgb(0.9, 0.2, 0.2),
+                    custom_size: Some(Vec2::new(METER_WIDTH, 0.)),
+                    anchor: Anchor::BottomCenter,
+                    ..Default::default()
+                },
+                // just outside the left wall, from the bottom of the board up
+                Transform::from_translation(Vec3::new(-2. * 32., -16., 1.)),
+            ))
+            .id();
+        commands
+            .entity(player)
+            .insert((AttackState::default(), IncomingGarbage::default()))
+            .add_child(meter);
+    }
+}
+
+/// a shape is locked once the player can no longer move it
+/// shapes cleared off the board or left with no blocks by a power were never placed
+fn lock_shape(
+    trigger: Trigger<OnRemove, PlayerTarget>,
+    shapes: Query<(&Shape, &PlayerTarget, &OnBoard, Has<Cleared>)>,
+    boards: Query<&Board>,
+    mut states: Query<&mut AttackState>,
+    clock: Res<GameClock>,
+) {
+    let Ok((shape, target, on_board, false)) = shapes.get(trigger.target()) else {
+        return;
+    };
+    if shape.blocks.is_empty() {
+        return;
+    }
+    let (Ok(board), Ok(mut state)) = (boards.get(on_board.0), states.get_mut(on_board.0)) else {
+        return;
+    };
+    if state.locked_on.replace(clock.tick) == Some(clock.tick) {
+        return;
+    }
+    state.spin = target.spun(shape, board);
+    if !state.cleared {
+        state.combo = 0;
+    }
+    state.cleared = false;
+}
+
+fn count_clears(mut events: EventReader<LinesCleared>, mut states: Query<&mut AttackState>) {
+    for LinesCleared { board, lines } in events.read() {
+        if let Ok(mut state) = states.get_mut(*board) {
+            state.lines += lines;
+        }
+    }
+}
+
+/// once a board settles its clears become an attack, which cancels its own garbage first
+pub(crate) fn send_attacks(
+    mut players: Query<(Entity, &Board, &mut AttackState, &mut IncomingGarbage)>,
+    tables: Res<Assets<AttackTable>>,
+    handle: Res<AttackTableHandle>,
+    clock: Res<GameClock>,
+    mut sent: EventWriter<AttackSent>,
+) {
+    let default_table = AttackTable::default();
+    let table = tables.get(&handle.0).unwrap_or(&default_table);
+    let mut attacks = Vec::new();
+    for (entity, board, mut state, mut incoming) in &mut players {
+        if board.has_moved() || state.lines == 0 {
+            continue;
+        }
+        let clear = Clear {
+            lines: state.lines,
+            spin: state.spin,
+            combo: state.combo,
+            back_to_back: state.back_to_back,
+        };
+        state.lines = 0;
+        state.spin = false;
+        state.combo += 1;
+        state.cleared = true;
+        state.back_to_back = clear.is_difficult();
+        let rows = incoming.cancel(table.attack(clear));
+        if rows > 0 {
+            attacks.push((entity, rows));
+        }
+    }
+    for (from, rows) in attacks {
+        sent.write(AttackSent { from, rows });
+        for (entity, _, _, mut incoming) in &mut players {
+            if entity != from {
+                incoming
+                    .queue
+                    .push_back((rows, clock.tick + table.delay_ticks));
+            }
+        }
+    }
+}
+
+fn raise_garbage(
+    mut players: Query<(Entity, &mut IncomingGarbage)>,
+    clock: Res<GameClock>,
+    mut garbage: EventWriter<AddGarbage>,
+) {
+    for (board, mut incoming) in &mut players {
+        while let Some(&(rows, ready)) = incoming.queue.front() {
+            if ready > clock.tick {
+                break;
+            }
+            incoming.queue.pop_front();
+            garbage.write(AddGarbage { board, rows });
+        }
+    }
+}
+
+fn update_meters(
+    incoming: Query<&IncomingGarbage, Changed<IncomingGarbage>>,
+    mut meters: Query<(&IncomingMeter, &mut Sprite)>,
+) {
+    for (meter, mut sprite) in &mut meters {
+        let Ok(incoming) = incoming.get(meter.0) else {
+            continue;
+        };
+        sprite.custom_size = Some(Vec2::new(METER_WIDTH, incoming.rows() as f32 * 32.));
+    }
+}
@@ -0,0 +1,167 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AttackTable>()
+        .init_asset_loader::<AttackLoader>()
+        .init_resource::<AttackTableHandle>();
+}
+
+/// the most rows any one entry of an attack table can send
+pub const MAX_ROWS: i32 = 20;
+
+/// how many rows of garbage each kind of clear is worth, read from an `.attack.ron` file
+#[derive(Asset, TypePath, Clone, serde::Deserialize)]
+pub struct AttackTable {
+    /// indexed by lines cleared at once, bigger clears use the last entry
+    pub lines: Vec<i32>,
+    /// used instead of `lines` when the shape was spun into place
+    pub spins: Vec<i32>,
+    /// added for each clear in a row, indexed by how many came before it
+    pub combo: Vec<i32>,
+    /// added when this and the last clear were both spins or four lines or more
+    pub back_to_back: i32,
+    /// fixed ticks queued garbage waits before it rises
+    pub delay_ticks: u64,
+}
+
+impl Default for AttackTable {
+    fn default() -> Self {
+        AttackTable {
+            lines: vec![0, 0, 1, 2, 4],
+            spins: vec![0, 2, 4, 6],
+            combo: vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5],
+            back_to_back: 1,
+            delay_ticks: 30,
+        }
+    }
+}
+
+/// a clear that has finished falling, ready to be turned into garbage
+#[derive(Clone, Copy, Debug)]
+pub struct Clear {
+    pub lines: i32,
+    pub spin: bool,
+    /// clears in a row before this one
+    pub combo: i32,
+    pub back_to_back: bool,
+}
+
+impl Clear {
+    /// spins and big clears keep a back to back going
+    pub fn is_difficult(&self) -> bool {
+        self.spin || self.lines >= 4
+    }
+}
+
+impl AttackTable {
+    pub fn attack(&self, clear: Clear) -> i32 {
+        let base = if clear.spin { &self.spins } else { &self.lines };
+        let mut rows = lookup(base, clear.lines) + lookup(&self.combo, clear.combo);
+        if clear.back_to_back && clear.is_difficult() {
+            rows += self.back_to_back;
+        }
+        rows
+    }
+
+    /// every entry has to send between 0 and `MAX_ROWS` rows
+    fn validate(&self) -> Result<(), AttackLoadError> {
+        let mut entries = self
+            .lines
+            .iter()
+            .chain(&self.spins)
+            .chain(&self.combo)
+            .chain(std::iter::once(&self.back_to_back));
+        match entries.find(|rows| !(0..=MAX_ROWS).contains(*rows)) {
+            Some(rows) => Err(AttackLoadError::Rows(*rows)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn lookup(table: &[i32], index: i32) -> i32 {
+    let index = index.max(0) as usize;
+    table
+        .get(index)
+        .or(table.last())
+        .copied()
+        .unwrap_or_default()
+}
+
+/// the table versus games use, the built in one stands in until it has loaded
+#[derive(Resource)]
+pub struct AttackTableHandle(pub Handle<AttackTable>);
+
+impl FromWorld for AttackTableHandle {
+    fn from_world(world: &mut World) -> Self {
+        AttackTableHandle(
+            world
+                .resource::<AssetServer>()
+                .load("versus/default.attack.ron"),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum AttackLoadError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Rows(i32),
+}
+
+impl std::fmt::Display for AttackLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttackLoadError::Io(e) => write!(f, "failed to read attack table: {e}"),
+            AttackLoadError::Ron(e) => write!(f, "failed to parse attack table: {e}"),
+            AttackLoadError::Rows(rows) => {
+                write!(
+                    f,
+                    "attack table sends {rows} rows, entries go from 0 to {MAX_ROWS}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttackLoadError {}
+
+impl From<std::io::Error> for AttackLoadError {
+    fn from(value: std::io::Error) -> Self {
+        AttackLoadError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for AttackLoadError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        AttackLoadError::Ron(value)
+    }
+}
+
+#[derive(Default)]
+struct AttackLoader;
+
+impl AssetLoader for AttackLoader {
+    type Asset = AttackTable;
+    type Settings = ();
+    type Error = AttackLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<AttackTable, AttackLoadError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let table = ron::de::from_bytes::<AttackTable>(&bytes)?;
+        table.validate()?;
+        Ok(table)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["attack.ron"]
+    }
+}